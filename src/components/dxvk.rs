@@ -42,6 +42,8 @@ pub struct Features {
     /// - `%temp%` - path to temp folder specified in config file
     /// - `%launcher%` - path to launcher folder
     /// - `%game%` - path to the game
+    ///
+    /// See `launch::keywords` module for other keywords. Use `%%` for literal `%`
     pub env: HashMap<String, String>,
 
     pub recommended: bool
@@ -1,3 +1,4 @@
 pub mod loader;
 pub mod wine;
 pub mod dxvk;
+pub mod runner;
@@ -0,0 +1,214 @@
+use std::path::{Path, PathBuf};
+use std::collections::HashMap;
+
+use wincompatlib::prelude::*;
+
+use super::wine::{self, Features};
+
+/// Find wine binary in the given build folder
+///
+/// `bin/wine64` is preferred, `bin/wine` is used by the builds
+/// made in the new WoW64 mode which have no separate 64 bit binary
+fn find_wine_binary(folder: impl AsRef<Path>) -> Option<PathBuf> {
+    ["bin/wine64", "bin/wine"].into_iter()
+        .map(|name| folder.as_ref().join(name))
+        .find(|path| path.is_file())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RunnerKind {
+    /// Wine build from the components index, started directly
+    Wine,
+
+    /// Proton build started through the `umu-run`
+    Proton
+}
+
+impl RunnerKind {
+    /// Detect kind of the runner placed in the given folder
+    ///
+    /// Returns `None` if the folder doesn't contain any runner
+    pub fn detect(folder: impl AsRef<Path>) -> Option<Self> {
+        let folder = folder.as_ref();
+
+        if Proton::from_folder(folder).is_some() {
+            Some(Self::Proton)
+        }
+
+        else if find_wine_binary(folder).is_some() {
+            Some(Self::Wine)
+        }
+
+        else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Proton build, e.g. Proton-GE
+pub struct Proton {
+    /// Name of the build folder
+    pub name: String,
+
+    /// Path to the build folder
+    pub path: PathBuf
+}
+
+impl Proton {
+    /// Try to read Proton build from the given folder
+    ///
+    /// The folder must contain `proton` script
+    /// and the wine build in `files` or `dist` folder
+    /// with `bin/wine64` or `bin/wine` binary
+    pub fn from_folder(folder: impl Into<PathBuf>) -> Option<Self> {
+        let path: PathBuf = folder.into();
+
+        if !path.join("proton").is_file() {
+            return None;
+        }
+
+        let proton = Self {
+            name: path.file_name()?.to_string_lossy().to_string(),
+            path
+        };
+
+        find_wine_binary(proton.get_wine_folder()).is_some().then_some(proton)
+    }
+
+    /// Get folder with the wine build used by proton
+    pub fn get_wine_folder(&self) -> PathBuf {
+        let files = self.path.join("files");
+
+        if find_wine_binary(&files).is_some() {
+            files
+        }
+
+        else {
+            self.path.join("dist")
+        }
+    }
+
+    #[inline]
+    pub fn get_wineserver(&self) -> PathBuf {
+        self.get_wine_folder().join("bin/wineserver")
+    }
+
+    /// Convert proton's wine build to the struct from `wincompatlib`
+    ///
+    /// This wine can be used to manage the prefix, but the game
+    /// should be started through the `umu-run`
+    pub fn to_wine(&self) -> Wine {
+        let wine_folder = self.get_wine_folder();
+
+        let wine = find_wine_binary(&wine_folder)
+            .unwrap_or_else(|| wine_folder.join("bin/wine64"));
+
+        Wine::from_binary(wine)
+            .with_loader(WineLoader::Current)
+            .with_arch(WineArch::Win64)
+            .with_boot(WineBoot::Unix(wine_folder.join("bin/wineboot")))
+            .with_server(self.get_wineserver())
+    }
+
+    /// Get environment variables needed to run the game with the `umu-run`
+    ///
+    /// `game_id` is the game id from the umu database
+    pub fn get_env_vars(&self, prefix: impl AsRef<Path>, game: impl AsRef<Path>, game_id: impl ToString) -> HashMap<&str, String> {
+        let prefix = prefix.as_ref().to_string_lossy().to_string();
+
+        HashMap::from([
+            ("PROTONPATH", self.path.to_string_lossy().to_string()),
+            ("GAMEID", game_id.to_string()),
+            ("WINEPREFIX", prefix.clone()),
+            ("STEAM_COMPAT_DATA_PATH", prefix),
+            ("STEAM_COMPAT_INSTALL_PATH", game.as_ref().to_string_lossy().to_string())
+        ])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Runner used to start the game
+pub enum Runner {
+    Wine(wine::Version),
+    Proton(Proton)
+}
+
+impl Runner {
+    /// Find runner with the given name
+    ///
+    /// Proton builds are detected in the `builds` folder,
+    /// wine builds are searched in the components index
+    pub fn find_in(components: impl Into<PathBuf>, builds: impl AsRef<Path>, name: impl AsRef<str>) -> anyhow::Result<Option<Self>> {
+        let name = name.as_ref();
+
+        if let Some(proton) = Proton::from_folder(builds.as_ref().join(name)) {
+            return Ok(Some(Self::Proton(proton)));
+        }
+
+        Ok(wine::Version::find_in(components, name)?.map(Self::Wine))
+    }
+
+    #[inline]
+    pub fn kind(&self) -> RunnerKind {
+        match self {
+            Self::Wine(_) => RunnerKind::Wine,
+            Self::Proton(_) => RunnerKind::Proton
+        }
+    }
+
+    #[inline]
+    /// Get name of the runner build folder
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Wine(wine) => &wine.name,
+            Self::Proton(proton) => &proton.name
+        }
+    }
+
+    /// Get features of the runner
+    ///
+    /// Proton builds are not listed in the components index
+    /// so they use default features
+    pub fn features(&self, components: impl Into<PathBuf>) -> anyhow::Result<Features> {
+        match self {
+            Self::Wine(wine) => Ok(wine.features(components)?.unwrap_or_default()),
+            Self::Proton(_) => Ok(Features::default())
+        }
+    }
+
+    /// Get path to the wineserver binary
+    ///
+    /// `folder` is the runner build folder
+    pub fn get_wineserver(&self, folder: impl AsRef<Path>) -> PathBuf {
+        match self {
+            Self::Wine(wine) => folder.as_ref().join(wine.files.wineserver.as_deref().unwrap_or("bin/wineserver")),
+            Self::Proton(proton) => proton.get_wineserver()
+        }
+    }
+
+    /// Convert runner to the wine struct from `wincompatlib`
+    ///
+    /// `folder` is the runner build folder
+    pub fn to_wine(&self, components: impl Into<PathBuf>, folder: impl Into<PathBuf>) -> Wine {
+        match self {
+            Self::Wine(wine) => wine.to_wine(components.into(), Some(folder.into())),
+            Self::Proton(proton) => proton.to_wine()
+        }
+    }
+}
+
+/// List proton builds downloaded to the given folder
+pub fn get_downloaded_proton(folder: impl AsRef<Path>) -> anyhow::Result<Vec<Proton>> {
+    let mut builds = Vec::new();
+
+    for entry in folder.as_ref().read_dir()? {
+        if let Some(proton) = Proton::from_folder(entry?.path()) {
+            builds.push(proton);
+        }
+    }
+
+    builds.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(builds)
+}
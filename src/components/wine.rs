@@ -40,12 +40,18 @@ pub struct Features {
 
     /// Command used to launch the game
     ///
+    /// It's split into arguments the same way as the shell does,
+    /// and keywords are replaced in every argument afterwards,
+    /// so they don't need to be quoted
+    ///
     /// Available keywords:
     /// - `%build%` - path to wine build
     /// - `%prefix%` - path to wine prefix
     /// - `%temp%` - path to temp folder specified in config file
     /// - `%launcher%` - path to launcher folder
     /// - `%game%` - path to the game
+    ///
+    /// See `launch::keywords` module for other keywords. Use `%%` for literal `%`
     pub command: Option<String>,
 
     /// Standard environment variables that are applied when you launch the game
@@ -56,6 +62,8 @@ pub struct Features {
     /// - `%temp%` - path to temp folder specified in config file
     /// - `%launcher%` - path to launcher folder
     /// - `%game%` - path to the game
+    ///
+    /// See `launch::keywords` module for other keywords. Use `%%` for literal `%`
     pub env: HashMap<String, String>,
 
     pub recommended: bool
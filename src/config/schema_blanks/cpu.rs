@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Serialize, Deserialize};
+use serde_json::Value as JsonValue;
+
+use enum_ordinalize::Ordinalize;
+
+/// File with the list of online CPU cores
+const ONLINE_CORES_FILE: &str = "/sys/devices/system/cpu/online";
+
+/// Max CPU core number supported by the linux kernel (`CONFIG_NR_CPUS`)
+const MAX_CORES: usize = 8192;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Ordinalize, Serialize, Deserialize)]
+/// IO scheduling class of the game process, see `man ionice`
+pub enum IoniceClass {
+    /// Don't change IO priority
+    None,
+
+    /// Priority level from `0` (highest) to `7` (lowest)
+    BestEffort,
+
+    /// Get disk access only when nobody else needs it
+    Idle
+}
+
+impl Default for IoniceClass {
+    #[inline]
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+impl From<&JsonValue> for IoniceClass {
+    #[inline]
+    fn from(value: &JsonValue) -> Self {
+        serde_json::from_value(value.clone()).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// CPU cores, scheduling priority and IO priority of the game
+pub struct CpuScheduling {
+    /// List of CPU cores the game runs on, e.g. `0-7,16-23`.
+    /// Empty to use all of them. Default is empty
+    pub cores: String,
+
+    /// Set `WINE_CPU_TOPOLOGY` from the cores list. Default is `true`
+    pub wine_topology: bool,
+
+    /// Niceness from `-20` (highest priority) to `19` (lowest).
+    /// Negative values need `CAP_SYS_NICE`. Default is `None`
+    pub nice: Option<i32>,
+
+    /// Default is `None`
+    pub ionice_class: IoniceClass,
+
+    /// Priority level of the best-effort class. Default is `4`
+    pub ionice_level: u8
+}
+
+impl Default for CpuScheduling {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            cores: String::new(),
+            wine_topology: true,
+            nice: None,
+            ionice_class: IoniceClass::default(),
+            ionice_level: 4
+        }
+    }
+}
+
+impl From<&JsonValue> for CpuScheduling {
+    fn from(value: &JsonValue) -> Self {
+        let default = Self::default();
+
+        Self {
+            cores: value.get("cores")
+                .and_then(JsonValue::as_str)
+                .map(|cores| cores.trim().to_string())
+                .unwrap_or(default.cores),
+
+            wine_topology: value.get("wine_topology")
+                .and_then(JsonValue::as_bool)
+                .unwrap_or(default.wine_topology),
+
+            nice: match value.get("nice") {
+                Some(value) => value.as_i64()
+                    .map(|nice| nice.clamp(-20, 19) as i32),
+
+                None => default.nice
+            },
+
+            ionice_class: value.get("ionice_class")
+                .map(IoniceClass::from)
+                .unwrap_or(default.ionice_class),
+
+            ionice_level: value.get("ionice_level")
+                .and_then(JsonValue::as_u64)
+                .map(|level| level.min(7) as u8)
+                .unwrap_or(default.ionice_level)
+        }
+    }
+}
+
+/// Parse list of CPU cores in the `taskset` / sysfs format
+///
+/// Returned cores are sorted and deduplicated. Cores above
+/// the kernel limit of 8192 are rejected
+///
+/// ```
+/// use anime_launcher_sdk::config::schema_blanks::cpu::parse_cores;
+///
+/// assert_eq!(parse_cores("0-3, 8,10-11,2").unwrap(), [0, 1, 2, 3, 8, 10, 11]);
+///
+/// assert!(parse_cores("3-1").is_err());
+/// assert!(parse_cores("0,a").is_err());
+/// assert!(parse_cores("0-18446744073709551615").is_err());
+/// ```
+pub fn parse_cores(list: impl AsRef<str>) -> anyhow::Result<Vec<usize>> {
+    let mut cores = Vec::new();
+
+    for part in list.as_ref().split(',').map(str::trim).filter(|part| !part.is_empty()) {
+        let parse = |core: &str| core.trim().parse::<usize>().ok()
+            .filter(|core| *core < MAX_CORES)
+            .ok_or_else(|| anyhow::anyhow!("Incorrect CPU core number: {core}"));
+
+        match part.split_once('-') {
+            Some((from, to)) => {
+                let (from, to) = (parse(from)?, parse(to)?);
+
+                if from > to {
+                    anyhow::bail!("Incorrect CPU cores range: {part}");
+                }
+
+                cores.extend(from..=to);
+            }
+
+            None => cores.push(parse(part)?)
+        }
+    }
+
+    cores.sort();
+    cores.dedup();
+
+    Ok(cores)
+}
+
+/// Get list of online CPU cores of the current system
+pub fn get_online_cores() -> anyhow::Result<Vec<usize>> {
+    parse_cores(std::fs::read_to_string(Path::new(ONLINE_CORES_FILE))?)
+}
+
+impl CpuScheduling {
+    /// Get configured cores list
+    ///
+    /// Returns `None` if cores are not set. Fails if some of them
+    /// are not online on the current system
+    pub fn get_cores(&self) -> anyhow::Result<Option<Vec<usize>>> {
+        if self.cores.trim().is_empty() {
+            return Ok(None);
+        }
+
+        self.get_cores_from(get_online_cores()?)
+    }
+
+    /// Get configured cores list validated against the given online cores
+    ///
+    /// ```
+    /// use anime_launcher_sdk::config::schema_blanks::cpu::CpuScheduling;
+    ///
+    /// let cpu = CpuScheduling {
+    ///     cores: String::from("2-3,6"),
+    ///     ..CpuScheduling::default()
+    /// };
+    ///
+    /// assert_eq!(cpu.get_cores_from(0..8).unwrap(), Some(vec![2, 3, 6]));
+    /// assert!(cpu.get_cores_from(0..4).is_err());
+    /// assert_eq!(CpuScheduling::default().get_cores_from(0..8).unwrap(), None);
+    /// ```
+    pub fn get_cores_from(&self, online: impl IntoIterator<Item = usize>) -> anyhow::Result<Option<Vec<usize>>> {
+        let cores = parse_cores(&self.cores)?;
+
+        if cores.is_empty() {
+            return Ok(None);
+        }
+
+        let online = online.into_iter().collect::<Vec<_>>();
+
+        let offline = cores.iter()
+            .filter(|core| !online.contains(core))
+            .map(usize::to_string)
+            .collect::<Vec<_>>();
+
+        if !offline.is_empty() {
+            anyhow::bail!("CPU cores {} are not available on this system", offline.join(", "));
+        }
+
+        Ok(Some(cores))
+    }
+
+    /// Get wrappers which apply the settings to the game process
+    ///
+    /// `cores` is the list returned by `get_cores`
+    ///
+    /// ```
+    /// use anime_launcher_sdk::config::schema_blanks::cpu::*;
+    ///
+    /// let cpu = CpuScheduling {
+    ///     nice: Some(-5),
+    ///     ionice_class: IoniceClass::BestEffort,
+    ///     ionice_level: 0,
+    ///     ..CpuScheduling::default()
+    /// };
+    ///
+    /// assert_eq!(cpu.get_wrappers(Some(&[0, 1, 2, 8])), [
+    ///     vec!["taskset", "-c", "0,1,2,8"],
+    ///     vec!["nice", "-n", "-5"],
+    ///     vec!["ionice", "-c", "2", "-n", "0"]
+    /// ]);
+    /// ```
+    pub fn get_wrappers(&self, cores: Option<&[usize]>) -> Vec<Vec<String>> {
+        let mut wrappers = Vec::new();
+
+        if let Some(cores) = cores {
+            wrappers.push(vec![
+                String::from("taskset"),
+                String::from("-c"),
+                join_cores(cores)
+            ]);
+        }
+
+        if let Some(nice) = self.nice {
+            wrappers.push(vec![
+                String::from("nice"),
+                String::from("-n"),
+                nice.to_string()
+            ]);
+        }
+
+        match self.ionice_class {
+            IoniceClass::None => (),
+
+            IoniceClass::BestEffort => wrappers.push(vec![
+                String::from("ionice"),
+                String::from("-c"),
+                String::from("2"),
+                String::from("-n"),
+                self.ionice_level.min(7).to_string()
+            ]),
+
+            IoniceClass::Idle => wrappers.push(vec![
+                String::from("ionice"),
+                String::from("-c"),
+                String::from("3")
+            ])
+        }
+
+        wrappers
+    }
+
+    /// Get environment variables which tell wine about the used cores
+    ///
+    /// ```
+    /// use anime_launcher_sdk::config::schema_blanks::cpu::CpuScheduling;
+    ///
+    /// let env = CpuScheduling::default().get_env_vars(Some(&[4, 5, 6, 7]));
+    ///
+    /// assert_eq!(env["WINE_CPU_TOPOLOGY"], "4:4,5,6,7");
+    /// assert!(CpuScheduling::default().get_env_vars(None).is_empty());
+    /// ```
+    pub fn get_env_vars(&self, cores: Option<&[usize]>) -> HashMap<&'static str, String> {
+        let mut env = HashMap::new();
+
+        if let Some(cores) = cores {
+            if self.wine_topology {
+                env.insert("WINE_CPU_TOPOLOGY", format!("{}:{}", cores.len(), join_cores(cores)));
+            }
+        }
+
+        env
+    }
+}
+
+fn join_cores(cores: &[usize]) -> String {
+    cores.iter()
+        .map(usize::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
@@ -1,6 +1,8 @@
 use serde::{Serialize, Deserialize};
 use serde_json::Value as JsonValue;
 
+use crate::shell;
+
 pub mod window_mode;
 pub mod window_size;
 pub mod framerate;
@@ -113,26 +115,55 @@ impl From<&JsonValue> for Gamescope {
 }
 
 impl Gamescope {
+    /// Get `gamescope [args]` command
+    ///
+    /// `extra_args` are appended as is
     pub fn get_command(&self) -> Option<String> {
         if !self.enabled {
             return None;
         }
 
+        let mut command = vec![String::from("gamescope")];
+
+        command.push(shell::join(self.get_flags()));
+        command.push(self.extra_args.trim().to_string());
+
+        let command = command.into_iter()
+            .filter(|flag| !flag.is_empty())
+            .collect::<Vec<_>>();
+
+        Some(command.join(" "))
+    }
+
+    /// Get `gamescope [args]` arguments list
+    ///
+    /// Fails if `extra_args` can't be parsed
+    pub fn get_args(&self) -> anyhow::Result<Option<Vec<String>>> {
+        if !self.enabled {
+            return Ok(None);
+        }
+
+        let mut args = vec![String::from("gamescope")];
+
+        args.extend(self.get_flags());
+        args.extend(shell::split(&self.extra_args)?);
+
+        Ok(Some(args))
+    }
+
+    fn get_flags(&self) -> Vec<String> {
         let flags = [
-            String::from("gamescope"),
             self.game_window.get_command("nested"),
             self.gamescope_window.get_command("output"),
             self.window_mode.get_flag().to_string(),
             self.framerate.get_command(),
             self.upscaling.get_command(),
-            self.options.get_command(),
-            self.extra_args.clone()
+            self.options.get_command()
         ];
 
-        let flags = flags.into_iter()
-            .filter(|flag| !flag.is_empty())
-            .collect::<Vec<_>>();
-
-        Some(flags.join(" "))
+        flags.iter()
+            .flat_map(|flag| flag.split_whitespace())
+            .map(String::from)
+            .collect()
     }
 }
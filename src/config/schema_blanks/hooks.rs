@@ -0,0 +1,95 @@
+use serde::{Serialize, Deserialize};
+use serde_json::Value as JsonValue;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Command executed before the game launch or after its exit
+///
+/// Hooks run outside of the sandbox with the same environment
+/// as the game. Keywords like `%prefix%` and `%game%` are replaced
+/// the same way as in the game launch command
+pub struct Hook {
+    /// Shell command to run
+    pub command: String,
+
+    /// Wait until the hook finishes. Otherwise the hook runs in background.
+    /// Default is `true`
+    pub blocking: bool,
+
+    /// Kill the hook if it runs longer than this amount of seconds.
+    /// `0` disables the timeout. Default is `60`
+    pub timeout: u64
+}
+
+impl Default for Hook {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            command: String::new(),
+            blocking: true,
+            timeout: 60
+        }
+    }
+}
+
+impl From<&JsonValue> for Hook {
+    fn from(value: &JsonValue) -> Self {
+        let default = Self::default();
+
+        // Allow to specify only the command
+        if let Some(command) = value.as_str() {
+            return Self {
+                command: command.to_string(),
+                ..default
+            };
+        }
+
+        Self {
+            command: value.get("command")
+                .and_then(JsonValue::as_str)
+                .map(String::from)
+                .unwrap_or(default.command),
+
+            blocking: value.get("blocking")
+                .and_then(JsonValue::as_bool)
+                .unwrap_or(default.blocking),
+
+            timeout: value.get("timeout")
+                .and_then(JsonValue::as_u64)
+                .unwrap_or(default.timeout)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameHooks {
+    /// Commands executed before the game launch
+    pub pre_launch: Vec<Hook>,
+
+    /// Commands executed after all the game processes have exited
+    pub post_exit: Vec<Hook>,
+
+    /// Commands executed when the game is going to be stopped
+    /// because of the playtime limits
+    pub playtime_warning: Vec<Hook>
+}
+
+impl From<&JsonValue> for GameHooks {
+    fn from(value: &JsonValue) -> Self {
+        let default = Self::default();
+
+        let get_hooks = |name: &str| {
+            value.get(name)
+                .and_then(JsonValue::as_array)
+                .map(|hooks| hooks.iter()
+                    .map(Hook::from)
+                    .filter(|hook| !hook.command.is_empty())
+                    .collect())
+        };
+
+        Self {
+            pre_launch: get_hooks("pre_launch").unwrap_or(default.pre_launch),
+            post_exit: get_hooks("post_exit").unwrap_or(default.post_exit),
+            playtime_warning: get_hooks("playtime_warning").unwrap_or(default.playtime_warning)
+        }
+    }
+}
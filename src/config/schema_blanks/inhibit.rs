@@ -0,0 +1,97 @@
+use serde::{Serialize, Deserialize};
+use serde_json::Value as JsonValue;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Prevent screen locking and system suspend while the game is running
+///
+/// Inhibitor is held by `systemd-inhibit` started before the game
+/// and stopped when all the game processes have exited
+pub struct SleepInhibit {
+    /// Default is `false`
+    pub enabled: bool,
+
+    /// Prevent screen locking and dimming. Default is `true`
+    pub idle: bool,
+
+    /// Prevent system suspend. Default is `true`
+    pub sleep: bool
+}
+
+impl Default for SleepInhibit {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            idle: true,
+            sleep: true
+        }
+    }
+}
+
+impl From<&JsonValue> for SleepInhibit {
+    fn from(value: &JsonValue) -> Self {
+        let default = Self::default();
+
+        Self {
+            enabled: value.get("enabled")
+                .and_then(JsonValue::as_bool)
+                .unwrap_or(default.enabled),
+
+            idle: value.get("idle")
+                .and_then(JsonValue::as_bool)
+                .unwrap_or(default.idle),
+
+            sleep: value.get("sleep")
+                .and_then(JsonValue::as_bool)
+                .unwrap_or(default.sleep)
+        }
+    }
+}
+
+impl SleepInhibit {
+    /// Get command which holds the inhibitor until it's killed
+    ///
+    /// `who` is the name of the application shown by the desktop.
+    /// Returns `None` if there's nothing to inhibit
+    ///
+    /// ```
+    /// use anime_launcher_sdk::config::schema_blanks::prelude::SleepInhibit;
+    ///
+    /// let inhibit = SleepInhibit {
+    ///     enabled: true,
+    ///     ..SleepInhibit::default()
+    /// };
+    ///
+    /// assert_eq!(inhibit.get_command("An Anime Game Launcher").unwrap(), [
+    ///     "systemd-inhibit",
+    ///     "--what=idle:sleep",
+    ///     "--who=An Anime Game Launcher",
+    ///     "--why=Game is running",
+    ///     "--mode=block",
+    ///     "sleep",
+    ///     "infinity"
+    /// ]);
+    ///
+    /// assert_eq!(SleepInhibit::default().get_command("An Anime Game Launcher"), None);
+    /// ```
+    pub fn get_command(&self, who: impl AsRef<str>) -> Option<Vec<String>> {
+        let what = [(self.idle, "idle"), (self.sleep, "sleep")]
+            .into_iter()
+            .filter_map(|(enabled, name)| enabled.then_some(name))
+            .collect::<Vec<_>>();
+
+        if !self.enabled || what.is_empty() {
+            return None;
+        }
+
+        Some(vec![
+            String::from("systemd-inhibit"),
+            format!("--what={}", what.join(":")),
+            format!("--who={}", who.as_ref()),
+            String::from("--why=Game is running"),
+            String::from("--mode=block"),
+            String::from("sleep"),
+            String::from("infinity")
+        ])
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Serialize, Deserialize};
+use serde_json::Value as JsonValue;
+
+/// Period of the CPU quota in microseconds, the same as used by systemd
+const CPU_PERIOD: u64 = 100000;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// Resource limits of the game process tree
+///
+/// Limits are applied with a `systemd-run --user --scope` if user systemd
+/// is running, or with a delegated cgroup v2 folder otherwise
+pub struct ResourceLimits {
+    /// Default is `false`
+    pub enabled: bool,
+
+    /// Max memory usage in MiB
+    pub memory_max: Option<u64>,
+
+    /// Max CPU time in percents of a single core, e.g. `200` for two cores
+    pub cpu_quota: Option<u64>,
+
+    /// IO weight from `1` to `10000`. Default weight of other processes is `100`
+    pub io_weight: Option<u64>,
+
+    /// Max number of processes and threads
+    pub tasks_max: Option<u64>,
+
+    /// Delegated cgroup v2 folder used when user systemd is not available,
+    /// e.g. `/sys/fs/cgroup/user.slice/user-1000.slice/user@1000.service/games`
+    pub cgroup: Option<PathBuf>
+}
+
+impl From<&JsonValue> for ResourceLimits {
+    fn from(value: &JsonValue) -> Self {
+        let default = Self::default();
+
+        // Allow to remove the limit with `null` or `0`
+        let get_limit = |name: &str, default: Option<u64>| match value.get(name) {
+            Some(value) => value.as_u64().filter(|limit| *limit > 0),
+            None => default
+        };
+
+        Self {
+            enabled: value.get("enabled")
+                .and_then(JsonValue::as_bool)
+                .unwrap_or(default.enabled),
+
+            memory_max: get_limit("memory_max", default.memory_max),
+            cpu_quota: get_limit("cpu_quota", default.cpu_quota),
+            io_weight: get_limit("io_weight", default.io_weight).map(|weight| weight.min(10000)),
+            tasks_max: get_limit("tasks_max", default.tasks_max),
+
+            cgroup: match value.get("cgroup") {
+                Some(value) => value.as_str()
+                    .filter(|path| !path.is_empty())
+                    .map(PathBuf::from),
+
+                None => default.cgroup
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Way used to apply the resource limits
+pub enum LimitsBackend {
+    /// `systemd-run --user --scope`
+    SystemdRun,
+
+    /// Child of the delegated cgroup v2 folder
+    Cgroup(PathBuf)
+}
+
+/// Check if the user systemd instance is running
+fn is_user_systemd_running() -> bool {
+    std::env::var("XDG_RUNTIME_DIR")
+        .map(|dir| Path::new(&dir).join("systemd/private").exists())
+        .unwrap_or(false)
+}
+
+/// Check if the cgroup v2 folder is delegated to the current user
+fn is_cgroup_delegated(path: &Path) -> bool {
+    let controllers = std::fs::read_to_string(path.join("cgroup.subtree_control"))
+        .unwrap_or_default();
+
+    // Opening the file for writing doesn't change it
+    let writable = std::fs::OpenOptions::new()
+        .write(true)
+        .open(path.join("cgroup.procs"))
+        .is_ok();
+
+    writable && ["memory", "cpu", "pids"].iter().all(|controller| {
+        controllers.split_whitespace().any(|name| name == *controller)
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Command which applies the resource limits to the wrapped command
+pub struct LimitsWrapper {
+    /// Wrapper binary followed by its arguments
+    pub command: Vec<String>,
+
+    /// Files which must be written before the launch
+    pub files: HashMap<PathBuf, String>
+}
+
+impl ResourceLimits {
+    /// Detect the way to apply the limits on the current system
+    ///
+    /// User systemd is preferred over the configured cgroup folder
+    pub fn detect_backend(&self) -> Option<LimitsBackend> {
+        if crate::is_available("systemd-run") && is_user_systemd_running() {
+            return Some(LimitsBackend::SystemdRun);
+        }
+
+        self.cgroup.as_ref()
+            .filter(|path| is_cgroup_delegated(path))
+            .map(|path| LimitsBackend::Cgroup(path.clone()))
+    }
+
+    /// Get `systemd-run` properties of the limits
+    ///
+    /// ```
+    /// use anime_launcher_sdk::config::schema_blanks::prelude::ResourceLimits;
+    ///
+    /// let limits = ResourceLimits::from(&serde_json::json!({
+    ///     "enabled": true,
+    ///     "memory_max": 8192,
+    ///     "cpu_quota": 400,
+    ///     "tasks_max": 0
+    /// }));
+    ///
+    /// assert_eq!(limits.get_properties(), ["MemoryMax=8192M", "CPUQuota=400%"]);
+    /// ```
+    pub fn get_properties(&self) -> Vec<String> {
+        let mut properties = Vec::new();
+
+        if let Some(memory) = self.memory_max {
+            properties.push(format!("MemoryMax={memory}M"));
+        }
+
+        if let Some(quota) = self.cpu_quota {
+            properties.push(format!("CPUQuota={quota}%"));
+        }
+
+        if let Some(weight) = self.io_weight {
+            properties.push(format!("IOWeight={weight}"));
+        }
+
+        if let Some(tasks) = self.tasks_max {
+            properties.push(format!("TasksMax={tasks}"));
+        }
+
+        properties
+    }
+
+    /// Get cgroup v2 interface files of the limits with their values
+    ///
+    /// ```
+    /// use anime_launcher_sdk::config::schema_blanks::prelude::ResourceLimits;
+    ///
+    /// let limits = ResourceLimits {
+    ///     enabled: true,
+    ///     memory_max: Some(1024),
+    ///     cpu_quota: Some(150),
+    ///     io_weight: Some(50),
+    ///     ..ResourceLimits::default()
+    /// };
+    ///
+    /// assert_eq!(limits.get_cgroup_values(), [
+    ///     ("memory.max", String::from("1073741824")),
+    ///     ("cpu.max", String::from("150000 100000")),
+    ///     ("io.weight", String::from("default 50"))
+    /// ]);
+    /// ```
+    pub fn get_cgroup_values(&self) -> Vec<(&'static str, String)> {
+        let mut values = Vec::new();
+
+        if let Some(memory) = self.memory_max {
+            values.push(("memory.max", memory.saturating_mul(1024 * 1024).to_string()));
+        }
+
+        if let Some(quota) = self.cpu_quota {
+            values.push(("cpu.max", format!("{} {CPU_PERIOD}", quota.saturating_mul(CPU_PERIOD) / 100)));
+        }
+
+        if let Some(weight) = self.io_weight {
+            values.push(("io.weight", format!("default {weight}")));
+        }
+
+        if let Some(tasks) = self.tasks_max {
+            values.push(("pids.max", tasks.to_string()));
+        }
+
+        values
+    }
+
+    /// Get wrapper which applies the limits with the given backend
+    ///
+    /// `name` is used to group the game processes: it's the name
+    /// of the systemd slice or of the child cgroup
+    ///
+    /// ```
+    /// use anime_launcher_sdk::config::schema_blanks::prelude::*;
+    ///
+    /// let limits = ResourceLimits {
+    ///     enabled: true,
+    ///     tasks_max: Some(4096),
+    ///     ..ResourceLimits::default()
+    /// };
+    ///
+    /// let wrapper = limits.get_wrapper_for(LimitsBackend::SystemdRun, "anime-game-launcher");
+    ///
+    /// assert_eq!(wrapper.command, [
+    ///     "systemd-run", "--user", "--scope", "--quiet", "--collect",
+    ///     "--slice=anime-game-launcher.slice",
+    ///     "-p", "TasksMax=4096"
+    /// ]);
+    ///
+    /// let wrapper = limits.get_wrapper_for(LimitsBackend::Cgroup("/sys/fs/cgroup/games".into()), "anime-game-launcher");
+    ///
+    /// assert_eq!(wrapper.command[3], "/sys/fs/cgroup/games/anime-game-launcher/cgroup.procs");
+    /// assert_eq!(wrapper.files[std::path::Path::new("/sys/fs/cgroup/games/anime-game-launcher/pids.max")], "4096");
+    ///
+    /// // The wrapper moves itself into the cgroup and then starts the wrapped command
+    /// let output = std::process::Command::new(&wrapper.command[0])
+    ///     .args(&wrapper.command[1..3])
+    ///     .args(["/dev/null", "echo", "game"])
+    ///     .output()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(output.stdout, b"game\n");
+    /// ```
+    pub fn get_wrapper_for(&self, backend: LimitsBackend, name: impl AsRef<str>) -> LimitsWrapper {
+        let name = name.as_ref();
+
+        match backend {
+            LimitsBackend::SystemdRun => {
+                let mut command = vec![
+                    String::from("systemd-run"),
+                    String::from("--user"),
+                    String::from("--scope"),
+                    String::from("--quiet"),
+                    String::from("--collect"),
+                    format!("--slice={name}.slice")
+                ];
+
+                for property in self.get_properties() {
+                    command.push(String::from("-p"));
+                    command.push(property);
+                }
+
+                LimitsWrapper {
+                    command,
+                    files: HashMap::new()
+                }
+            }
+
+            LimitsBackend::Cgroup(path) => {
+                let cgroup = path.join(name);
+
+                LimitsWrapper {
+                    command: vec![
+                        String::from("sh"),
+                        String::from("-c"),
+                        String::from("echo $$ > \"$0\" && exec \"$@\""),
+                        cgroup.join("cgroup.procs").to_string_lossy().to_string()
+                    ],
+
+                    files: self.get_cgroup_values().into_iter()
+                        .map(|(file, value)| (cgroup.join(file), value))
+                        .collect()
+                }
+            }
+        }
+    }
+
+    /// Get wrapper which applies the limits on the current system
+    ///
+    /// Returns `None` if the limits are disabled, or if neither
+    /// user systemd nor delegated cgroup is available
+    pub fn get_wrapper(&self, name: impl AsRef<str>) -> Option<LimitsWrapper> {
+        if !self.enabled {
+            return None;
+        }
+
+        let Some(backend) = self.detect_backend()
+        else {
+            tracing::warn!("Neither user systemd nor delegated cgroup is available. Resource limits are not applied");
+
+            return None;
+        };
+
+        Some(self.get_wrapper_for(backend, name))
+    }
+}
@@ -0,0 +1,39 @@
+use serde::{Serialize, Deserialize};
+use serde_json::Value as JsonValue;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameLogs {
+    /// Amount of stored game sessions logs. Default is `10`
+    pub retention: u64,
+
+    /// Compress logs of the previous sessions with zstd. Default is `false`
+    ///
+    /// Requires `logs-compression` feature
+    pub compress: bool
+}
+
+impl Default for GameLogs {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            retention: 10,
+            compress: false
+        }
+    }
+}
+
+impl From<&JsonValue> for GameLogs {
+    fn from(value: &JsonValue) -> Self {
+        let default = Self::default();
+
+        Self {
+            retention: value.get("retention")
+                .and_then(JsonValue::as_u64)
+                .unwrap_or(default.retention),
+
+            compress: value.get("compress")
+                .and_then(JsonValue::as_bool)
+                .unwrap_or(default.compress)
+        }
+    }
+}
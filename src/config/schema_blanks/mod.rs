@@ -1,5 +1,12 @@
 pub mod resolution;
 pub mod repairer;
+pub mod logs;
+pub mod hooks;
+pub mod wrappers;
+pub mod limits;
+pub mod cpu;
+pub mod inhibit;
+pub mod playtime;
 pub mod fsr;
 pub mod hud;
 pub mod fps;
@@ -16,6 +23,13 @@ pub mod sandbox;
 pub mod prelude {
     pub use super::resolution::Resolution;
     pub use super::repairer::Repairer;
+    pub use super::logs::GameLogs;
+    pub use super::hooks::{Hook, GameHooks};
+    pub use super::wrappers::{CustomWrapper, WrapperPosition};
+    pub use super::limits::{ResourceLimits, LimitsBackend, LimitsWrapper};
+    pub use super::cpu::{CpuScheduling, IoniceClass};
+    pub use super::inhibit::SleepInhibit;
+    pub use super::playtime::{PlaytimeLimits, PlaytimeBudget, TimeWindow};
     pub use super::fsr::*;
     pub use super::hud::HUD;
     pub use super::fps::Fps;
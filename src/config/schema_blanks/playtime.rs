@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+use serde_json::Value as JsonValue;
+
+/// Amount of seconds in a day
+const DAY: u64 = 86400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Time of the day when the game can be played
+pub struct TimeWindow {
+    /// Minute of the day when the window starts
+    pub from: u64,
+
+    /// Minute of the day when the window ends. Windows which end
+    /// before they start continue on the next day
+    pub to: u64
+}
+
+impl TimeWindow {
+    /// Parse window in the `HH:MM-HH:MM` format
+    ///
+    /// ```
+    /// use anime_launcher_sdk::config::schema_blanks::playtime::TimeWindow;
+    ///
+    /// assert_eq!(TimeWindow::parse("16:00-20:30").unwrap(), TimeWindow { from: 960, to: 1230 });
+    /// assert_eq!(TimeWindow::parse(" 22:00 - 2:00 ").unwrap(), TimeWindow { from: 1320, to: 120 });
+    ///
+    /// assert!(TimeWindow::parse("16:00").is_err());
+    /// assert!(TimeWindow::parse("16:00-24:30").is_err());
+    /// ```
+    pub fn parse(window: impl AsRef<str>) -> anyhow::Result<Self> {
+        let window = window.as_ref();
+
+        let parse = |time: &str| -> Option<u64> {
+            let (hours, minutes) = time.trim().split_once(':')?;
+
+            let hours = hours.parse::<u64>().ok().filter(|hours| *hours < 24)?;
+            let minutes = minutes.parse::<u64>().ok().filter(|minutes| *minutes < 60)?;
+
+            Some(hours * 60 + minutes)
+        };
+
+        let Some((Some(from), Some(to))) = window.split_once('-').map(|(from, to)| (parse(from), parse(to)))
+        else {
+            anyhow::bail!("Incorrect playtime window: {window}");
+        };
+
+        Ok(Self { from, to })
+    }
+
+    /// Get amount of seconds left until the window end
+    ///
+    /// Returns `None` if the given second of the day is outside of the window.
+    /// Windows with the same start and end time last the whole day
+    ///
+    /// ```
+    /// use anime_launcher_sdk::config::schema_blanks::playtime::TimeWindow;
+    ///
+    /// let window = TimeWindow::parse("22:00-02:00").unwrap();
+    ///
+    /// assert_eq!(window.get_seconds_left(23 * 3600), Some(3 * 3600));
+    /// assert_eq!(window.get_seconds_left(3600), Some(3600));
+    /// assert_eq!(window.get_seconds_left(12 * 3600), None);
+    /// ```
+    pub fn get_seconds_left(&self, second: u64) -> Option<u64> {
+        let from = self.from * 60;
+        let to = self.to * 60;
+
+        let end = if from < to {
+            (from..to).contains(&second).then_some(to)?
+        }
+
+        else if second >= from {
+            to + DAY
+        }
+
+        else if second < to {
+            to
+        }
+
+        else {
+            return None;
+        };
+
+        Some(end - second)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// Amount of time the game can be played
+pub struct PlaytimeBudget {
+    /// Max playtime per day in minutes
+    pub daily_minutes: Option<u64>,
+
+    /// Local time windows when the game can be played, e.g. `18:00-20:00`.
+    /// Empty to allow any time
+    pub windows: Vec<String>
+}
+
+impl From<&JsonValue> for PlaytimeBudget {
+    fn from(value: &JsonValue) -> Self {
+        let default = Self::default();
+
+        Self {
+            daily_minutes: match value.get("daily_minutes") {
+                Some(value) => value.as_u64(),
+                None => default.daily_minutes
+            },
+
+            windows: value.get("windows")
+                .and_then(JsonValue::as_array)
+                .map(|windows| windows.iter()
+                    .filter_map(JsonValue::as_str)
+                    .map(String::from)
+                    .collect())
+                .unwrap_or(default.windows)
+        }
+    }
+}
+
+impl PlaytimeBudget {
+    #[inline]
+    /// Parse time windows of the budget
+    ///
+    /// Fails if some of them are incorrect so mistakes
+    /// in the config don't remove the limits
+    pub fn get_windows(&self) -> anyhow::Result<Vec<TimeWindow>> {
+        self.windows.iter()
+            .map(TimeWindow::parse)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Limits of the game playtime
+///
+/// Playtime is accounted using the playtime history. Budget of the game
+/// counts sessions of all the accounts, while budgets of the `Sessions`
+/// accounts replace it and count sessions of their account only
+///
+/// ```
+/// use anime_launcher_sdk::config::schema_blanks::playtime::*;
+///
+/// let limits = PlaytimeLimits::from(&serde_json::json!({
+///     "enabled": true,
+///     "daily_minutes": 90,
+///     "accounts": {
+///         "kid": {
+///             "daily_minutes": 30,
+///             "windows": ["16:00-19:00"]
+///         }
+///     }
+/// }));
+///
+/// assert_eq!(limits.get_budget(None).daily_minutes, Some(90));
+/// assert_eq!(limits.get_budget(Some("parent")).daily_minutes, Some(90));
+/// assert_eq!(limits.get_budget(Some("kid")).windows, ["16:00-19:00"]);
+/// assert_eq!(limits.warning_minutes, 5);
+/// ```
+pub struct PlaytimeLimits {
+    /// Default is `false`
+    pub enabled: bool,
+
+    /// Budget of the game
+    #[serde(flatten)]
+    pub budget: PlaytimeBudget,
+
+    /// Budgets of the `Sessions` accounts
+    pub accounts: HashMap<String, PlaytimeBudget>,
+
+    /// Run playtime warning hooks this amount of minutes
+    /// before the game is stopped. Default is `5`
+    pub warning_minutes: u64
+}
+
+impl Default for PlaytimeLimits {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            budget: PlaytimeBudget::default(),
+            accounts: HashMap::new(),
+            warning_minutes: 5
+        }
+    }
+}
+
+impl From<&JsonValue> for PlaytimeLimits {
+    fn from(value: &JsonValue) -> Self {
+        let default = Self::default();
+
+        Self {
+            enabled: value.get("enabled")
+                .and_then(JsonValue::as_bool)
+                .unwrap_or(default.enabled),
+
+            budget: PlaytimeBudget::from(value),
+
+            accounts: value.get("accounts")
+                .and_then(JsonValue::as_object)
+                .map(|accounts| accounts.iter()
+                    .map(|(name, budget)| (name.clone(), PlaytimeBudget::from(budget)))
+                    .collect())
+                .unwrap_or(default.accounts),
+
+            warning_minutes: value.get("warning_minutes")
+                .and_then(JsonValue::as_u64)
+                .unwrap_or(default.warning_minutes)
+        }
+    }
+}
+
+impl PlaytimeLimits {
+    #[inline]
+    /// Get budget of the given account, or of the game if the account has no own one
+    pub fn get_budget(&self, account: Option<&str>) -> &PlaytimeBudget {
+        account.and_then(|account| self.accounts.get(account))
+            .unwrap_or(&self.budget)
+    }
+
+    #[inline]
+    /// Check if the given account has its own budget
+    pub fn has_account_budget(&self, account: Option<&str>) -> bool {
+        account.is_some_and(|account| self.accounts.contains_key(account))
+    }
+}
@@ -0,0 +1,202 @@
+use std::path::PathBuf;
+
+use serde::{Serialize, Deserialize};
+use serde_json::Value as JsonValue;
+
+use super::SandboxPath;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Filter access to the session D-Bus from the sandbox
+///
+/// With bwrap the game gets the `xdg-dbus-proxy` socket instead
+/// of the real session bus. Firejail filters the bus by itself.
+///
+/// Abstract bus sockets can't be hidden since the sandbox
+/// shares the network namespace with the host
+pub struct SandboxDbus {
+    /// Default is `false`
+    pub enabled: bool,
+
+    /// Bus names the game can talk to. Default is
+    /// `org.freedesktop.Notifications` and `org.freedesktop.ScreenSaver`.
+    ///
+    /// PulseAudio over D-Bus needs `org.PulseAudio1`
+    pub talk: Vec<String>,
+
+    /// Bus names the game can own. Default is empty
+    pub own: Vec<String>
+}
+
+impl Default for SandboxDbus {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            enabled: false,
+
+            talk: vec![
+                String::from("org.freedesktop.Notifications"),
+                String::from("org.freedesktop.ScreenSaver")
+            ],
+
+            own: vec![]
+        }
+    }
+}
+
+impl From<&JsonValue> for SandboxDbus {
+    fn from(value: &JsonValue) -> Self {
+        let default = Self::default();
+
+        let names = |value: Option<&JsonValue>| value
+            .and_then(JsonValue::as_array)
+            .map(|values| values.iter()
+                .filter_map(JsonValue::as_str)
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect::<Vec<_>>());
+
+        Self {
+            enabled: value.get("enabled")
+                .and_then(JsonValue::as_bool)
+                .unwrap_or(default.enabled),
+
+            talk: names(value.get("talk")).unwrap_or(default.talk),
+            own: names(value.get("own")).unwrap_or(default.own)
+        }
+    }
+}
+
+/// Get address of the current session bus
+///
+/// Uses `DBUS_SESSION_BUS_ADDRESS` or the `$XDG_RUNTIME_DIR/bus` socket
+pub fn get_bus_address() -> Option<String> {
+    if let Ok(address) = std::env::var("DBUS_SESSION_BUS_ADDRESS") {
+        if !address.trim().is_empty() {
+            return Some(address.trim().to_string());
+        }
+    }
+
+    let socket = PathBuf::from(std::env::var("XDG_RUNTIME_DIR").ok()?).join("bus");
+
+    socket.exists().then(|| format!("unix:path={}", socket.to_string_lossy()))
+}
+
+/// Get path of the bus socket from its address
+///
+/// Returns `None` if the address doesn't use filesystem socket
+///
+/// ```
+/// use anime_launcher_sdk::config::schema_blanks::sandbox::dbus::get_bus_path;
+///
+/// assert_eq!(get_bus_path("unix:path=/run/user/1000/bus").as_deref(), Some("/run/user/1000/bus"));
+/// assert_eq!(get_bus_path("unix:abstract=/tmp/dbus-1,guid=1;unix:path=/tmp/bus,guid=1").as_deref(), Some("/tmp/bus"));
+/// assert_eq!(get_bus_path("unix:abstract=/tmp/dbus-1"), None);
+/// ```
+pub fn get_bus_path(address: impl AsRef<str>) -> Option<String> {
+    address.as_ref()
+        .split(';')
+        .filter_map(|address| address.strip_prefix("unix:"))
+        .flat_map(|params| params.split(','))
+        .find_map(|param| param.strip_prefix("path="))
+        .map(String::from)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// `xdg-dbus-proxy` started outside of the sandbox
+///
+/// ```
+/// use anime_launcher_sdk::config::schema_blanks::sandbox::dbus::*;
+///
+/// let dbus = SandboxDbus {
+///     enabled: true,
+///     own: vec![String::from("com.example.Game")],
+///     ..SandboxDbus::default()
+/// };
+///
+/// let proxy = DbusProxy::new(&dbus, "unix:path=/run/user/1000/bus", "/run/user/1000/proxy");
+///
+/// assert_eq!(proxy.command, [
+///     "xdg-dbus-proxy",
+///     "unix:path=/run/user/1000/bus",
+///     "/run/user/1000/proxy",
+///     "--filter",
+///     "--talk=org.freedesktop.Notifications",
+///     "--talk=org.freedesktop.ScreenSaver",
+///     "--own=com.example.Game"
+/// ]);
+///
+/// // Proxy socket replaces the real bus in the sandbox
+/// assert_eq!(proxy.get_path().host, "/run/user/1000/proxy");
+/// assert_eq!(proxy.get_path().guest, "/run/user/1000/bus");
+/// assert_eq!(proxy.get_address(), "unix:path=/run/user/1000/proxy");
+/// ```
+pub struct DbusProxy {
+    /// Proxy binary followed by its arguments
+    pub command: Vec<String>,
+
+    /// Path to the proxy socket on the host system
+    pub socket: String,
+
+    /// Path to the proxy socket inside of the sandbox
+    pub guest: String
+}
+
+impl DbusProxy {
+    pub fn new(dbus: &SandboxDbus, address: impl ToString, socket: impl ToString) -> Self {
+        let address = address.to_string();
+        let socket = socket.to_string();
+
+        let mut command = vec![
+            String::from("xdg-dbus-proxy"),
+            address.clone(),
+            socket.clone(),
+            String::from("--filter")
+        ];
+
+        command.extend(dbus.talk.iter().map(|name| format!("--talk={name}")));
+        command.extend(dbus.own.iter().map(|name| format!("--own={name}")));
+
+        Self {
+            command,
+
+            // Abstract sockets can't be replaced, so the proxy
+            // is placed next to the other sandbox paths
+            guest: get_bus_path(&address)
+                .unwrap_or_else(|| String::from("/tmp/sandbox/dbus")),
+
+            socket
+        }
+    }
+
+    /// Get default path to the proxy socket on the host system
+    ///
+    /// The path is unique for every call so several games
+    /// can be started at the same time
+    pub fn get_socket_path() -> PathBuf {
+        let folder = std::env::var("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| std::env::temp_dir());
+
+        let time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+
+        folder.join(format!("anime-launcher-dbus-{}-{time}", std::process::id()))
+    }
+
+    #[inline]
+    /// Get mount of the proxy socket which replaces the real bus in the sandbox
+    pub fn get_path(&self) -> SandboxPath {
+        SandboxPath::bind(&self.socket, &self.guest)
+    }
+
+    #[inline]
+    /// Get bus address of the proxy with the host path
+    ///
+    /// The path is translated to the sandbox one together
+    /// with the rest of the environment
+    pub fn get_address(&self) -> String {
+        format!("unix:path={}", self.socket)
+    }
+}
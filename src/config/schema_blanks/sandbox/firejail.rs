@@ -0,0 +1,155 @@
+use std::fmt::Display;
+
+use super::{Sandbox, SandboxMount, SandboxMountKind};
+use super::hosts::HOSTS_FILE;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Firejail profile generated from the sandbox settings
+///
+/// Firejail can't mount host paths to other places nor mount tmpfs
+/// without root privileges, so mounted paths must be available
+/// at the same place inside of the sandbox. Home isolation is done
+/// with whitelisting, and private paths are blacklisted.
+/// Read-only file mounted to `/etc/hosts` is used as the hosts file.
+/// Session D-Bus is filtered by firejail itself
+///
+/// ```
+/// use anime_launcher_sdk::config::schema_blanks::prelude::*;
+///
+/// let sandbox = Sandbox {
+///     hostname: Some(String::from("anime")),
+///     isolate_home: false,
+///     ..Sandbox::default()
+/// };
+///
+/// let profile = FirejailProfile::new(&sandbox, &[
+///     SandboxMount::bind("/games/An Anime Game", "/games/An Anime Game"),
+///     SandboxMount::read_only("/opt/wine", "/opt/wine"),
+///     SandboxMount::tmpfs("/secret"),
+///     SandboxMount::read_only("/tmp/hosts", "/etc/hosts")
+/// ]).unwrap();
+///
+/// assert_eq!(profile.to_string(), "read-only /opt/wine\nblacklist /secret\nhosts-file /tmp/hosts\nhostname anime\n");
+/// assert_eq!(profile.get_args(), ["--read-only=/opt/wine", "--blacklist=/secret", "--hosts-file=/tmp/hosts", "--hostname=anime"]);
+///
+/// let sandbox = Sandbox {
+///     dbus: SandboxDbus {
+///         enabled: true,
+///         talk: vec![String::from("org.freedesktop.Notifications")],
+///         own: vec![]
+///     },
+///     ..sandbox
+/// };
+///
+/// assert_eq!(FirejailProfile::new(&sandbox, &[]).unwrap().get_args(), [
+///     "--hostname=anime",
+///     "--dbus-user=filter",
+///     "--dbus-user.talk=org.freedesktop.Notifications"
+/// ]);
+///
+/// // Paths can't be moved to other places
+/// assert!(FirejailProfile::new(&sandbox, &[SandboxMount::bind("/games/game", "/tmp/sandbox/game")]).is_err());
+/// ```
+pub struct FirejailProfile {
+    directives: Vec<(String, Option<String>)>
+}
+
+impl FirejailProfile {
+    /// Build profile from the sandbox settings and mounts list
+    ///
+    /// Fails if some mount can't be reproduced by firejail
+    pub fn new(sandbox: &Sandbox, mounts: &[SandboxMount]) -> anyhow::Result<Self> {
+        let mut profile = Self::default();
+
+        let home = std::env::var("HOME").ok()
+            .map(|home| home.trim().trim_end_matches('/').to_string())
+            .filter(|home| !home.is_empty());
+
+        let in_home = |path: &str| home.as_ref().is_some_and(|home| {
+            path.strip_prefix(home.as_str()).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        });
+
+        let mut whitelisted = false;
+
+        for mount in mounts {
+            match (mount.kind, &mount.source) {
+                (SandboxMountKind::Tmpfs, _) => profile.add("blacklist", Some(&mount.target)),
+
+                (SandboxMountKind::ReadOnly, Some(source)) if mount.target == HOSTS_FILE => {
+                    profile.add("hosts-file", Some(source));
+                }
+
+                (SandboxMountKind::Symlink, _) => {
+                    anyhow::bail!("Firejail can't create symlink {} in the sandbox", mount.target);
+                }
+
+                (kind, Some(source)) if source == &mount.target => {
+                    if sandbox.isolate_home && in_home(source) {
+                        profile.add("whitelist", Some(source));
+
+                        whitelisted = true;
+                    }
+
+                    if kind == SandboxMountKind::ReadOnly {
+                        profile.add("read-only", Some(source));
+                    }
+                }
+
+                (_, source) => {
+                    anyhow::bail!("Firejail can't mount {} to {} in the sandbox", source.as_deref().unwrap_or_default(), mount.target);
+                }
+            }
+        }
+
+        // Whitelisting any home path already hides the rest of it
+        if sandbox.isolate_home && !whitelisted {
+            profile.add("private", None::<String>);
+        }
+
+        if let Some(hostname) = &sandbox.hostname {
+            profile.add("hostname", Some(hostname));
+        }
+
+        if sandbox.dbus.enabled {
+            profile.add("dbus-user", Some("filter"));
+
+            for name in &sandbox.dbus.talk {
+                profile.add("dbus-user.talk", Some(name));
+            }
+
+            for name in &sandbox.dbus.own {
+                profile.add("dbus-user.own", Some(name));
+            }
+        }
+
+        Ok(profile)
+    }
+
+    fn add(&mut self, name: impl ToString, value: Option<impl ToString>) {
+        self.directives.push((name.to_string(), value.map(|value| value.to_string())));
+    }
+
+    /// Get firejail command line arguments equal to the profile
+    pub fn get_args(&self) -> Vec<String> {
+        self.directives.iter()
+            .map(|(name, value)| match value {
+                Some(value) => format!("--{name}={value}"),
+                None => format!("--{name}")
+            })
+            .collect()
+    }
+}
+
+impl Display for FirejailProfile {
+    /// Get content of the profile file
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (name, value) in &self.directives {
+            match value {
+                Some(value) => writeln!(f, "{name} {value}")?,
+                None => writeln!(f, "{name}")?
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+/// Path to the hosts file inside of the sandbox
+pub const HOSTS_FILE: &str = "/etc/hosts";
+
+/// Read the system hosts file
+///
+/// Returns minimal localhost entries if the file can't be read
+pub fn read_system_hosts() -> String {
+    std::fs::read_to_string(HOSTS_FILE)
+        .unwrap_or_else(|_| String::from("127.0.0.1 localhost\n::1 localhost\n"))
+}
+
+/// Generate hosts file which keeps the system entries
+/// and maps the given domains to `0.0.0.0`
+///
+/// ```
+/// use anime_launcher_sdk::config::schema_blanks::sandbox::hosts::generate_hosts_file;
+///
+/// let hosts = generate_hosts_file("127.0.0.1 localhost", ["log-upload-os.hoyoverse.com"]);
+///
+/// assert_eq!(hosts, "127.0.0.1 localhost\n\n# Blocked in the sandbox\n0.0.0.0 log-upload-os.hoyoverse.com\n");
+/// ```
+pub fn generate_hosts_file<T: AsRef<str>>(system_hosts: impl AsRef<str>, blocked: impl IntoIterator<Item = T>) -> String {
+    let mut hosts = system_hosts.as_ref().to_string();
+
+    if !hosts.is_empty() && !hosts.ends_with('\n') {
+        hosts.push('\n');
+    }
+
+    hosts.push_str("\n# Blocked in the sandbox\n");
+
+    for domain in blocked {
+        hosts.push_str(&format!("0.0.0.0 {}\n", domain.as_ref()));
+    }
+
+    hosts
+}
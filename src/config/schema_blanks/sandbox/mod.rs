@@ -3,29 +3,130 @@ use std::path::Path;
 use serde::{Serialize, Deserialize};
 use serde_json::Value as JsonValue;
 
+use enum_ordinalize::Ordinalize;
+
+use crate::shell;
+
 mod mounts;
+mod paths;
+mod firejail;
+mod presets;
+mod validation;
+
+pub mod dbus;
+pub mod hosts;
 
 pub use mounts::Mounts;
+pub use paths::{SandboxPath, SandboxPaths};
+pub use firejail::FirejailProfile;
+pub use dbus::{SandboxDbus, DbusProxy};
+pub use presets::SandboxPreset;
+pub use validation::SandboxProblem;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ordinalize, Serialize, Deserialize)]
+pub enum SandboxBackend {
+    /// Use `bwrap`. Requires unprivileged user namespaces
+    Bwrap,
+
+    /// Use setuid `firejail`. Paths can't be moved to other places
+    /// in the sandbox with it, so they keep their host paths
+    Firejail,
+
+    /// Don't use sandbox
+    None
+}
+
+impl Default for SandboxBackend {
+    #[inline]
+    fn default() -> Self {
+        Self::Bwrap
+    }
+}
+
+impl From<&JsonValue> for SandboxBackend {
+    #[inline]
+    fn from(value: &JsonValue) -> Self {
+        serde_json::from_value(value.clone()).unwrap_or_default()
+    }
+}
+
+/// Check if unprivileged user namespaces are not disabled by the system
+fn user_namespaces_allowed() -> bool {
+    let read = |path: &str| std::fs::read_to_string(path).ok()
+        .and_then(|value| value.trim().parse::<u64>().ok());
+
+    // Debian-based kernels
+    if read("/proc/sys/kernel/unprivileged_userns_clone") == Some(0) {
+        return false;
+    }
+
+    read("/proc/sys/user/max_user_namespaces") != Some(0)
+}
+
+impl SandboxBackend {
+    #[inline]
+    /// Get name of the backend binary
+    pub fn binary(&self) -> Option<&'static str> {
+        match self {
+            Self::Bwrap    => Some("bwrap"),
+            Self::Firejail => Some("firejail"),
+            Self::None     => None
+        }
+    }
+
+    /// Check if the backend can be used on the current system
+    pub fn is_available(&self) -> bool {
+        match self {
+            Self::Bwrap => crate::is_available("bwrap") && user_namespaces_allowed(),
+            Self::Firejail => crate::is_available("firejail"),
+            Self::None => true
+        }
+    }
+
+    /// Get the first backend available on the current system
+    ///
+    /// Returns `None` variant if neither bwrap nor firejail can be used
+    pub fn detect() -> Self {
+        [Self::Bwrap, Self::Firejail].into_iter()
+            .find(Self::is_available)
+            .unwrap_or(Self::None)
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Sandbox {
-    /// Use `bwrap` to run the game. Default is `false`
+    /// Use sandbox to run the game. Default is `false`
     pub enabled: bool,
 
+    /// Preset used as the base of the settings. Settings
+    /// missing in the config are taken from it. Default is `None`
+    pub preset: Option<SandboxPreset>,
+
+    /// Program used to create the sandbox. Default is `Bwrap`
+    pub backend: SandboxBackend,
+
     /// Mount tmpfs to `/home`, `/var/home/$USER` and `$HOME`. Default is `true`
     pub isolate_home: bool,
 
     /// Spoof original hostname. Default is `None`
     pub hostname: Option<String>,
 
-    /// Append additional bwrap arguments. Default is `None`
+    /// Append additional bwrap or firejail arguments. Default is `None`
     pub args: Option<String>,
 
     /// List of paths to which tmpfs will be mounted. Default is empty
     pub private: Vec<String>,
 
+    /// Block game telemetry servers with the generated hosts file
+    /// mounted over `/etc/hosts`, so the system one doesn't need
+    /// to be edited. Default is `true`
+    pub block_telemetry: bool,
+
     /// Maps of directories mounts
-    pub mounts: Mounts
+    pub mounts: Mounts,
+
+    /// Session D-Bus filtering
+    pub dbus: SandboxDbus
 }
 
 impl Default for Sandbox {
@@ -33,24 +134,38 @@ impl Default for Sandbox {
     fn default() -> Self {
         Self {
             enabled: false,
+            preset: None,
+            backend: SandboxBackend::default(),
             isolate_home: true,
             hostname: None,
             args: None,
             private: vec![],
-            mounts: Mounts::default()
+            block_telemetry: true,
+            mounts: Mounts::default(),
+            dbus: SandboxDbus::default()
         }
     }
 }
 
 impl From<&JsonValue> for Sandbox {
     fn from(value: &JsonValue) -> Self {
-        let default = Self::default();
+        let preset = value.get("preset")
+            .and_then(|preset| serde_json::from_value::<SandboxPreset>(preset.clone()).ok());
+
+        let default = preset.map(|preset| preset.get_sandbox())
+            .unwrap_or_default();
 
         Self {
             enabled: value.get("enabled")
                 .and_then(JsonValue::as_bool)
                 .unwrap_or(default.enabled),
 
+            preset,
+
+            backend: value.get("backend")
+                .map(SandboxBackend::from)
+                .unwrap_or(default.backend),
+
             isolate_home: value.get("isolate_home")
                 .and_then(JsonValue::as_bool)
                 .unwrap_or(default.isolate_home),
@@ -96,18 +211,267 @@ impl From<&JsonValue> for Sandbox {
                 None => default.private
             },
 
+            block_telemetry: value.get("block_telemetry")
+                .and_then(JsonValue::as_bool)
+                .unwrap_or(default.block_telemetry),
+
             mounts: value.get("mounts")
                 .map(Mounts::from)
-                .unwrap_or(default.mounts)
+                .unwrap_or(default.mounts),
+
+            dbus: value.get("dbus")
+                .map(SandboxDbus::from)
+                .unwrap_or(default.dbus)
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SandboxMountKind {
+    /// `--ro-bind <source> <target>`
+    ReadOnly,
+
+    /// `--bind <source> <target>`
+    Bind,
+
+    /// `--dev-bind <source> <target>`
+    DevBind,
+
+    /// `--tmpfs <target>`
+    Tmpfs,
+
+    /// `--symlink <source> <target>`
+    Symlink
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SandboxMount {
+    pub kind: SandboxMountKind,
+
+    /// Host path. Always `None` for tmpfs mounts
+    pub source: Option<String>,
+
+    /// Path inside of the sandbox
+    pub target: String
+}
+
+impl SandboxMount {
+    #[inline]
+    pub fn read_only(source: impl ToString, target: impl ToString) -> Self {
+        Self {
+            kind: SandboxMountKind::ReadOnly,
+            source: Some(source.to_string()),
+            target: target.to_string()
+        }
+    }
+
+    #[inline]
+    pub fn bind(source: impl ToString, target: impl ToString) -> Self {
+        Self {
+            kind: SandboxMountKind::Bind,
+            source: Some(source.to_string()),
+            target: target.to_string()
+        }
+    }
+
+    #[inline]
+    pub fn dev_bind(source: impl ToString, target: impl ToString) -> Self {
+        Self {
+            kind: SandboxMountKind::DevBind,
+            source: Some(source.to_string()),
+            target: target.to_string()
+        }
+    }
+
+    #[inline]
+    pub fn tmpfs(target: impl ToString) -> Self {
+        Self {
+            kind: SandboxMountKind::Tmpfs,
+            source: None,
+            target: target.to_string()
+        }
+    }
+
+    #[inline]
+    pub fn symlink(source: impl ToString, target: impl ToString) -> Self {
+        Self {
+            kind: SandboxMountKind::Symlink,
+            source: Some(source.to_string()),
+            target: target.to_string()
+        }
+    }
+
+    /// Get bwrap arguments for the current mount
+    ///
+    /// ```
+    /// use anime_launcher_sdk::config::schema_blanks::prelude::SandboxMount;
+    ///
+    /// let mount = SandboxMount::bind("/games/it's \"my\" $game", "/tmp/sandbox/game");
+    ///
+    /// assert_eq!(mount.get_args(), ["--bind", "/games/it's \"my\" $game", "/tmp/sandbox/game"]);
+    /// ```
+    pub fn get_args(&self) -> Vec<String> {
+        let flag = match self.kind {
+            SandboxMountKind::ReadOnly => "--ro-bind",
+            SandboxMountKind::Bind     => "--bind",
+            SandboxMountKind::DevBind  => "--dev-bind",
+            SandboxMountKind::Tmpfs    => "--tmpfs",
+            SandboxMountKind::Symlink  => "--symlink"
+        };
+
+        let mut args = vec![flag.to_string()];
+
+        if let Some(source) = &self.source {
+            args.push(source.clone());
+        }
+
+        args.push(self.target.clone());
+
+        args
+    }
+
+    #[inline]
+    /// Get escaped bwrap arguments for the current mount
+    ///
+    /// ```
+    /// use anime_launcher_sdk::config::schema_blanks::prelude::SandboxMount;
+    ///
+    /// let mount = SandboxMount::bind("/games/it's $game", "/tmp/sandbox/game");
+    ///
+    /// assert_eq!(mount.get_command(), "--bind '/games/it'\\''s $game' /tmp/sandbox/game");
+    /// ```
+    pub fn get_command(&self) -> String {
+        shell::join(self.get_args())
+    }
+}
+
 impl Sandbox {
-    /// Return `bwrap [args]` command
-    /// 
+    #[inline]
+    /// Check if the game should be started in the sandbox
+    pub fn is_enabled(&self) -> bool {
+        self.enabled && self.backend != SandboxBackend::None
+    }
+
+    #[inline]
+    /// Find mistakes in the user-given mounts and private paths
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use anime_launcher_sdk::config::schema_blanks::prelude::*;
+    ///
+    /// let sandbox = Sandbox {
+    ///     private: vec![String::from("/tmp/sandbox/game/secret")],
+    ///     mounts: Mounts {
+    ///         read_only: HashMap::from([(String::from("/"), String::from("/host"))]),
+    ///         bind: HashMap::from([(String::from("/definitely/missing/path"), String::from("/host/data/"))]),
+    ///         ..Mounts::default()
+    ///     },
+    ///     ..Sandbox::default()
+    /// };
+    ///
+    /// assert_eq!(sandbox.get_problems(), [
+    ///     SandboxProblem::MissingSource {
+    ///         source: String::from("/definitely/missing/path"),
+    ///         target: String::from("/host/data")
+    ///     },
+    ///     SandboxProblem::OverlappingTargets(String::from("/host"), String::from("/host/data")),
+    ///     SandboxProblem::ReservedTarget(String::from("/tmp/sandbox/game/secret"))
+    /// ]);
+    ///
+    /// assert!(sandbox.validate().is_err());
+    /// assert!(Sandbox::default().validate().is_ok());
+    /// ```
+    pub fn get_problems(&self) -> Vec<SandboxProblem> {
+        validation::get_problems(self)
+    }
+
+    /// Check the sandbox settings before launching the game
+    ///
+    /// Fails with the list of problems, see `get_problems`
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let problems = self.get_problems();
+
+        if !problems.is_empty() {
+            let problems = problems.iter()
+                .map(SandboxProblem::to_string)
+                .collect::<Vec<_>>();
+
+            anyhow::bail!("Incorrect sandbox settings: {}", problems.join(", "));
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    /// Check if the telemetry servers are blocked in the sandbox
+    pub fn blocks_telemetry(&self) -> bool {
+        self.is_enabled() && self.block_telemetry
+    }
+
+    /// Place the path according to the selected backend
+    ///
+    /// Firejail can't move paths to other places in the sandbox,
+    /// so they're kept at their host places
+    ///
+    /// ```
+    /// use anime_launcher_sdk::config::schema_blanks::prelude::*;
+    ///
+    /// let mut sandbox = Sandbox::default();
+    ///
+    /// assert_eq!(sandbox.map_path(SandboxPath::bind("/games/game", "/tmp/sandbox/game")).guest, "/tmp/sandbox/game");
+    ///
+    /// sandbox.backend = SandboxBackend::Firejail;
+    ///
+    /// assert_eq!(sandbox.map_path(SandboxPath::bind("/games/game", "/tmp/sandbox/game")).guest, "/games/game");
+    /// ```
+    pub fn map_path(&self, path: SandboxPath) -> SandboxPath {
+        match self.backend {
+            SandboxBackend::Firejail => SandboxPath {
+                guest: path.host.clone(),
+                ..path
+            },
+
+            _ => path
+        }
+    }
+
+    /// Get map of the host paths available in the sandbox
+    ///
+    /// ### Paths:
+    ///
+    /// | Original | Mounted | Type |
+    /// | :- | :- | :- |
+    /// | <mounts/read_only> | <mounts/read_only> | read-only bind |
+    /// | <mounts/binds> | <mounts/binds> | bind |
+    /// | `wine_dir` | `/tmp/sandbox/wine` | bind |
+    /// | `prefix_dir` | `/tmp/sandbox/prefix` | bind |
+    /// | `game_dir` | `/tmp/sandbox/game` | bind |
+    ///
+    /// Firejail backend keeps all the paths at their host places.
+    /// Fails if some of these paths collide
+    pub fn get_paths(&self, wine_dir: impl AsRef<str>, prefix_dir: impl AsRef<str>, game_dir: impl AsRef<str>) -> anyhow::Result<SandboxPaths> {
+        let mut paths = SandboxPaths::default();
+
+        for (from, to) in &self.mounts.read_only {
+            paths.insert(self.map_path(SandboxPath::read_only(from, to)))?;
+        }
+
+        for (from, to) in &self.mounts.bind {
+            paths.insert(self.map_path(SandboxPath::bind(from, to)))?;
+        }
+
+        paths.insert(self.map_path(SandboxPath::bind(wine_dir, "/tmp/sandbox/wine")))?;
+        paths.insert(self.map_path(SandboxPath::bind(prefix_dir, "/tmp/sandbox/prefix")))?;
+        paths.insert(self.map_path(SandboxPath::bind(game_dir, "/tmp/sandbox/game")))?;
+
+        Ok(paths)
+    }
+
+    /// Get list of mounts used by the sandbox
+    ///
     /// ### Mounts:
-    /// 
+    ///
     /// | Original | Mounted | Type | Optional |
     /// | :- | :- | :- | :- |
     /// | `/` | `/` | read-only bind | false |
@@ -117,37 +481,38 @@ impl Sandbox {
     /// | - | `/home` | tmpfs | true |
     /// | - | `/var/home/$USER` | tmpfs | true |
     /// | - | `$HOME` | tmpfs | true |
-    /// | `wine_dir` | `/tmp/sandbox/wine` | bind | false |
-    /// | `prefix_dir` | `/tmp/sandbox/prefix` | bind | false |
-    /// | `game_dir` | `/tmp/sandbox/game` | bind | false |
-    /// | <mounts/read_only> | <mounts/read_only> | read-only bind | true |
-    /// | <mounts/binds> | <mounts/binds> | bind | true |
+    /// | <private> | <private> | tmpfs | true |
     /// | <mounts/symlinks> | <mounts/symlinks> | symlink | true |
-    pub fn get_command(&self, wine_dir: impl AsRef<str>, prefix_dir: impl AsRef<str>, game_dir: impl AsRef<str>) -> String {
-        let mut command = String::from("bwrap --ro-bind / /");
-
-        command.push_str(" --bind /tmp /tmp");
-        command.push_str(" --bind /proc /proc");
-        command.push_str(" --dev-bind /dev /dev");
-
-        if let Some(hostname) = &self.hostname {
-            command += &format!(" --hostname '{hostname}'");
+    /// | <paths> | <paths> | see `get_paths` | false |
+    ///
+    /// Firejail backend uses only private paths, symlinks and `paths` mounts
+    /// since the rest is done by firejail itself, see `FirejailProfile`
+    pub fn get_mounts(&self, paths: &SandboxPaths) -> Vec<SandboxMount> {
+        let mut mounts = Vec::new();
+
+        if self.backend != SandboxBackend::Firejail {
+            mounts.extend([
+                SandboxMount::read_only("/", "/"),
+                SandboxMount::bind("/tmp", "/tmp"),
+                SandboxMount::bind("/proc", "/proc"),
+                SandboxMount::dev_bind("/dev", "/dev")
+            ]);
         }
 
-        if self.isolate_home {
+        if self.isolate_home && self.backend != SandboxBackend::Firejail {
             if Path::new("/home").is_dir() {
-                command.push_str(" --tmpfs /home");
+                mounts.push(SandboxMount::tmpfs("/home"));
             }
 
             if Path::new("/var/home").is_dir() {
-                command.push_str(" --tmpfs /var/home");
+                mounts.push(SandboxMount::tmpfs("/var/home"));
             }
 
             if let Ok(user) = std::env::var("USER") {
                 let dir = format!("/var/home/{}", user.trim());
 
                 if Path::new(&dir).is_dir() {
-                    command += &format!(" --tmpfs '{dir}'");
+                    mounts.push(SandboxMount::tmpfs(dir));
                 }
             }
 
@@ -155,44 +520,158 @@ impl Sandbox {
                 let dir = home.trim();
 
                 if Path::new(&dir).is_dir() {
-                    command += &format!(" --tmpfs '{dir}'");
+                    mounts.push(SandboxMount::tmpfs(dir));
                 }
             }
         }
 
         for path in &self.private {
-            command += &format!(" --tmpfs '{}'", path.trim());
+            mounts.push(SandboxMount::tmpfs(path.trim()));
         }
 
-        for (from, to) in &self.mounts.read_only {
-            command += &format!(" --ro-bind '{}' '{}'", from.trim(), to.trim());
+        for (from, to) in &self.mounts.symlinks {
+            mounts.push(SandboxMount::symlink(from.trim(), to.trim()));
         }
 
-        for (from, to) in &self.mounts.bind {
-            command += &format!(" --bind '{}' '{}'", from.trim(), to.trim());
+        mounts.extend(paths.get_mounts());
+
+        mounts
+    }
+
+    /// Return `bwrap [args]` or `firejail [args]` arguments list
+    /// with given mounts list
+    ///
+    /// Fails if user-given `args` can't be parsed, or if firejail
+    /// can't reproduce some of the mounts
+    ///
+    /// ```
+    /// use anime_launcher_sdk::config::schema_blanks::prelude::*;
+    ///
+    /// let sandbox = Sandbox {
+    ///     hostname: Some(String::from("it's $(hostname)")),
+    ///     args: Some(String::from("--setenv NAME 'An Anime Game'")),
+    ///     ..Sandbox::default()
+    /// };
+    ///
+    /// let args = sandbox.get_args_with_mounts(&[
+    ///     SandboxMount::bind("/games/\"An\" Anime Game's `id`", "/tmp/sandbox/game")
+    /// ]).unwrap();
+    ///
+    /// assert_eq!(&args[..4], ["bwrap", "--bind", "/games/\"An\" Anime Game's `id`", "/tmp/sandbox/game"]);
+    /// assert_eq!(&args[4..6], ["--hostname", "it's $(hostname)"]);
+    /// assert_eq!(&args[args.len() - 3..], ["--setenv", "NAME", "An Anime Game"]);
+    /// ```
+    pub fn get_args_with_mounts(&self, mounts: &[SandboxMount]) -> anyhow::Result<Vec<String>> {
+        let mut args = self.get_base_args(mounts)?;
+
+        if let Some(extra_args) = &self.args {
+            args.extend(shell::split(extra_args)?);
         }
 
-        for (from, to) in &self.mounts.symlinks {
-            command += &format!(" --symlink '{}' '{}'", from.trim(), to.trim());
+        Ok(args)
+    }
+
+    /// Return `bwrap [args]` or `firejail [args]` command with given mounts list
+    ///
+    /// All the paths are escaped, user-given `args` are appended as is
+    pub fn get_command_with_mounts(&self, mounts: &[SandboxMount]) -> anyhow::Result<String> {
+        let mut command = shell::join(self.get_base_args(mounts)?);
+
+        if let Some(args) = &self.args {
+            command.push(' ');
+            command.push_str(args.trim());
         }
 
-        command += &format!(" --bind '{}' /tmp/sandbox/wine", wine_dir.as_ref());
-        command += &format!(" --bind '{}' /tmp/sandbox/prefix", prefix_dir.as_ref());
-        command += &format!(" --bind '{}' /tmp/sandbox/game", game_dir.as_ref());
+        Ok(command)
+    }
+
+    #[inline]
+    /// Return `bwrap [args]` or `firejail [args]` command
+    ///
+    /// See `get_mounts` and `get_paths` for the list of used mounts
+    pub fn get_command(&self, wine_dir: impl AsRef<str>, prefix_dir: impl AsRef<str>, game_dir: impl AsRef<str>) -> anyhow::Result<String> {
+        self.get_command_with_mounts(&self.get_mounts(&self.get_paths(wine_dir, prefix_dir, game_dir)?))
+    }
 
-        command.push_str(" --die-with-parent");
+    /// Get arguments which set the working directory in the sandbox
+    ///
+    /// Firejail keeps the current directory, so it doesn't need them
+    pub fn get_chdir_args(&self, dir: impl ToString) -> Vec<String> {
+        match self.backend {
+            SandboxBackend::Bwrap => vec![String::from("--chdir"), dir.to_string()],
+            _ => vec![]
+        }
+    }
 
-        // --unshare-pid breaks wine
+    /// Get D-Bus proxy which should be started before the game
+    ///
+    /// Returns `None` if D-Bus filtering is disabled or if it's done
+    /// by the sandbox backend itself. Fails if the session bus is not found
+    ///
+    /// The proxy socket must be added to the sandbox paths, and the game
+    /// should get its address in the `DBUS_SESSION_BUS_ADDRESS` variable
+    pub fn get_dbus_proxy(&self) -> anyhow::Result<Option<DbusProxy>> {
+        if !self.is_enabled() || !self.dbus.enabled || self.backend != SandboxBackend::Bwrap {
+            return Ok(None);
+        }
 
-        command.push_str(" --unshare-user");
-        command.push_str(" --unshare-ipc");
-        command.push_str(" --unshare-uts");
-        command.push_str(" --unshare-cgroup");
+        let Some(address) = dbus::get_bus_address()
+        else {
+            anyhow::bail!("Couldn't find session D-Bus address");
+        };
 
-        if let Some(args) = &self.args {
-            command.push_str(args.trim());
+        let socket = DbusProxy::get_socket_path();
+
+        Ok(Some(DbusProxy::new(&self.dbus, address, socket.to_string_lossy())))
+    }
+
+    #[inline]
+    /// Get firejail profile with given mounts list
+    ///
+    /// See `FirejailProfile`
+    pub fn get_firejail_profile(&self, mounts: &[SandboxMount]) -> anyhow::Result<FirejailProfile> {
+        FirejailProfile::new(self, mounts)
+    }
+
+    fn get_base_args(&self, mounts: &[SandboxMount]) -> anyhow::Result<Vec<String>> {
+        match self.backend {
+            SandboxBackend::Bwrap => Ok(self.get_bwrap_args(mounts)),
+
+            SandboxBackend::Firejail => {
+                let mut args = vec![
+                    String::from("firejail"),
+                    String::from("--quiet"),
+                    String::from("--noprofile")
+                ];
+
+                args.extend(self.get_firejail_profile(mounts)?.get_args());
+
+                Ok(args)
+            }
+
+            SandboxBackend::None => anyhow::bail!("Sandbox backend is not selected")
         }
+    }
+
+    fn get_bwrap_args(&self, mounts: &[SandboxMount]) -> Vec<String> {
+        let mut args = vec![String::from("bwrap")];
+
+        args.extend(mounts.iter().flat_map(SandboxMount::get_args));
+
+        if let Some(hostname) = &self.hostname {
+            args.push(String::from("--hostname"));
+            args.push(hostname.clone());
+        }
+
+        args.push(String::from("--die-with-parent"));
+
+        // --unshare-pid breaks wine
+
+        args.push(String::from("--unshare-user"));
+        args.push(String::from("--unshare-ipc"));
+        args.push(String::from("--unshare-uts"));
+        args.push(String::from("--unshare-cgroup"));
 
-        command
+        args
     }
 }
@@ -0,0 +1,218 @@
+use super::{SandboxMount, SandboxMountKind};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Host path available inside of the sandbox
+pub struct SandboxPath {
+    /// Path on the host system
+    pub host: String,
+
+    /// Path inside of the sandbox
+    pub guest: String,
+
+    /// How the host path is mounted to the sandbox.
+    /// `None` if the path is only translated, e.g. when
+    /// the guest path is already available in the sandbox
+    pub mount: Option<SandboxMountKind>
+}
+
+impl SandboxPath {
+    #[inline]
+    pub fn bind(host: impl AsRef<str>, guest: impl AsRef<str>) -> Self {
+        Self::new(host, guest, Some(SandboxMountKind::Bind))
+    }
+
+    #[inline]
+    pub fn read_only(host: impl AsRef<str>, guest: impl AsRef<str>) -> Self {
+        Self::new(host, guest, Some(SandboxMountKind::ReadOnly))
+    }
+
+    #[inline]
+    /// Translate host path without mounting it
+    pub fn translate(host: impl AsRef<str>, guest: impl AsRef<str>) -> Self {
+        Self::new(host, guest, None)
+    }
+
+    fn new(host: impl AsRef<str>, guest: impl AsRef<str>, mount: Option<SandboxMountKind>) -> Self {
+        Self {
+            host: normalize(host.as_ref()),
+            guest: normalize(guest.as_ref()),
+            mount
+        }
+    }
+
+    /// Get mount of the current path if it's mounted
+    pub fn get_mount(&self) -> Option<SandboxMount> {
+        let kind = self.mount?;
+
+        Some(SandboxMount {
+            kind,
+            source: Some(self.host.clone()),
+            target: self.guest.clone()
+        })
+    }
+}
+
+/// Remove trailing slashes from the path
+pub(super) fn normalize(path: &str) -> String {
+    let path = path.trim();
+    let trimmed = path.trim_end_matches('/');
+
+    if trimmed.is_empty() && !path.is_empty() {
+        String::from("/")
+    }
+
+    else {
+        trimmed.to_string()
+    }
+}
+
+/// Check if the symbol can be a part of a file name
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || "_-.~+@".contains(c)
+}
+
+/// Check if `path` equals to `parent` or is placed inside of it
+pub(super) fn is_inside(path: &str, parent: &str) -> bool {
+    parent == "/" || path == parent || path.strip_prefix(parent).is_some_and(|rest| rest.starts_with('/'))
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Map of the host paths to the sandbox ones
+///
+/// Paths are translated as a whole, so nested paths like a wine prefix
+/// inside of the game folder are translated using the most specific entry.
+/// Ambiguous entries are rejected when they're inserted
+///
+/// ```
+/// use anime_launcher_sdk::config::schema_blanks::prelude::*;
+///
+/// let mut paths = SandboxPaths::default();
+///
+/// paths.insert(SandboxPath::bind("/games/game", "/tmp/sandbox/game")).unwrap();
+/// paths.insert(SandboxPath::bind("/games/game/prefix", "/tmp/sandbox/prefix")).unwrap();
+/// paths.insert(SandboxPath::translate("/games/game-temp", "/tmp")).unwrap();
+///
+/// assert_eq!(
+///     paths.translate("Z:\\/games/game/game.exe /games/game/prefix/drive_c:/games/game-temp/x /games/game2"),
+///     "Z:\\/tmp/sandbox/game/game.exe /tmp/sandbox/prefix/drive_c:/tmp/x /games/game2"
+/// );
+///
+/// // The same host path can't be mounted twice
+/// assert!(paths.insert(SandboxPath::bind("/games/game/", "/tmp/sandbox/other")).is_err());
+///
+/// // Two host paths can't share the same sandbox path
+/// assert!(paths.insert(SandboxPath::bind("/games/other", "/tmp/sandbox/game")).is_err());
+///
+/// // Mount can't hide a part of another one
+/// assert!(paths.insert(SandboxPath::bind("/games/dlc", "/tmp/sandbox/game/dlc")).is_err());
+/// ```
+pub struct SandboxPaths {
+    paths: Vec<SandboxPath>
+}
+
+impl SandboxPaths {
+    #[inline]
+    pub fn iter(&self) -> std::slice::Iter<'_, SandboxPath> {
+        self.paths.iter()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Add path to the map
+    ///
+    /// Fails if the path collides with already added ones
+    pub fn insert(&mut self, path: SandboxPath) -> anyhow::Result<()> {
+        if path.host.is_empty() || path.guest.is_empty() {
+            anyhow::bail!("Sandbox paths can't be empty");
+        }
+
+        for known in &self.paths {
+            if known.host == path.host {
+                if known.guest == path.guest {
+                    return Ok(());
+                }
+
+                anyhow::bail!("Host path {} is mapped to both {} and {} in the sandbox", path.host, known.guest, path.guest);
+            }
+
+            if known.guest == path.guest {
+                anyhow::bail!("Host paths {} and {} are both mapped to {} in the sandbox", known.host, path.host, path.guest);
+            }
+
+            // Mounted folder would hide a part of another mounted folder
+            if known.mount.is_some() && path.mount.is_some() {
+                let (outer, inner) = if is_inside(&path.guest, &known.guest) {
+                    (known, &path)
+                } else if is_inside(&known.guest, &path.guest) {
+                    (&path, known)
+                } else {
+                    continue;
+                };
+
+                let expected_host = format!("{}{}", outer.host, &inner.guest[outer.guest.len()..]);
+
+                if inner.host != expected_host {
+                    anyhow::bail!("Sandbox path {} of {} is placed inside of {} mounted from {}", inner.guest, inner.host, outer.guest, outer.host);
+                }
+            }
+        }
+
+        self.paths.push(path);
+
+        Ok(())
+    }
+
+    /// Get mounts of the mapped paths
+    pub fn get_mounts(&self) -> Vec<SandboxMount> {
+        self.paths.iter()
+            .filter_map(SandboxPath::get_mount)
+            .collect()
+    }
+
+    /// Replace host paths in the given value with the sandbox ones
+    ///
+    /// Paths are matched as a whole: `/games/game` is not replaced
+    /// in `/games/game2`
+    pub fn translate(&self, value: impl AsRef<str>) -> String {
+        let value = value.as_ref();
+
+        let mut result = String::with_capacity(value.len());
+        let mut prev = None;
+        let mut i = 0;
+
+        while i < value.len() {
+            let rest = &value[i..];
+
+            let path = prev.is_none_or(|prev| !is_name_char(prev))
+                .then(|| {
+                    self.paths.iter()
+                        .filter(|path| path.host != path.guest && path.host != "/")
+                        .filter(|path| {
+                            rest.strip_prefix(path.host.as_str())
+                                .is_some_and(|tail| !tail.starts_with(is_name_char))
+                        })
+                        .max_by_key(|path| path.host.len())
+                })
+                .flatten();
+
+            if let Some(path) = path {
+                result += &path.guest;
+
+                i += path.host.len();
+                prev = path.host.chars().last();
+            }
+
+            else if let Some(c) = rest.chars().next() {
+                result.push(c);
+
+                i += c.len_utf8();
+                prev = Some(c);
+            }
+        }
+
+        result
+    }
+}
@@ -0,0 +1,138 @@
+use std::path::Path;
+
+use serde::{Serialize, Deserialize};
+
+use enum_ordinalize::Ordinalize;
+
+use super::{Sandbox, SandboxDbus, Mounts};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ordinalize, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Named set of the sandbox settings
+///
+/// Preset is the base of the sandbox settings: values set
+/// in the config override the preset ones
+///
+/// ```
+/// use anime_launcher_sdk::config::schema_blanks::prelude::*;
+///
+/// std::env::set_var("HOME", "/home/anime");
+///
+/// // Home is mounted over the tmpfs in read-only mode
+/// let sandbox = Sandbox::from(&serde_json::json!({
+///     "preset": "home-readonly",
+///     "hostname": "anime"
+/// }));
+///
+/// assert_eq!(sandbox.preset, Some(SandboxPreset::HomeReadOnly));
+/// assert_eq!(sandbox.hostname.as_deref(), Some("anime"));
+///
+/// let paths = sandbox.get_paths("/games/wine", "/games/prefix", "/games/game").unwrap();
+/// let args = sandbox.get_args_with_mounts(&sandbox.get_mounts(&paths)).unwrap();
+///
+/// assert!(args.windows(3).any(|arg| arg == ["--ro-bind", "/home/anime", "/home/anime"]));
+/// assert!(!args.windows(2).any(|arg| arg == ["--tmpfs", "/home"]));
+///
+/// // D-Bus is filtered without any allowed names
+/// let sandbox = Sandbox::from(&serde_json::json!({
+///     "preset": "strict",
+///     "isolate_home": false
+/// }));
+///
+/// assert!(!sandbox.isolate_home);
+/// assert!(sandbox.dbus.enabled && sandbox.dbus.talk.is_empty());
+///
+/// let sandbox = Sandbox {
+///     backend: SandboxBackend::Firejail,
+///     ..sandbox
+/// };
+///
+/// assert_eq!(sandbox.get_args_with_mounts(&[]).unwrap(), [
+///     "firejail", "--quiet", "--noprofile",
+///     "--hostname=localhost",
+///     "--dbus-user=filter"
+/// ]);
+/// ```
+pub enum SandboxPreset {
+    /// Isolated home, spoofed hostname and session D-Bus without any allowed names
+    Strict,
+
+    /// Home is available in read-only mode, so only
+    /// the game folders can be modified
+    #[serde(rename = "home-readonly")]
+    HomeReadOnly,
+
+    /// Isolated home with read-only configs of network
+    /// wrappers like `proxychains` or `torsocks`
+    NetworkTools
+}
+
+/// Config files of the network wrappers relative to the home folder
+const NETWORK_TOOLS_CONFIGS: &[&str] = &[
+    ".proxychains",
+    ".config/proxychains",
+    ".torsocks.conf"
+];
+
+impl SandboxPreset {
+    /// Get sandbox settings of the preset
+    ///
+    /// Presets don't enable the sandbox and don't change its backend
+    pub fn get_sandbox(&self) -> Sandbox {
+        let home = std::env::var("HOME").ok()
+            .map(|home| home.trim().trim_end_matches('/').to_string())
+            .filter(|home| !home.is_empty());
+
+        match self {
+            Self::Strict => Sandbox {
+                preset: Some(*self),
+                isolate_home: true,
+                hostname: Some(String::from("localhost")),
+                block_telemetry: true,
+
+                dbus: SandboxDbus {
+                    enabled: true,
+                    talk: vec![],
+                    own: vec![]
+                },
+
+                ..Sandbox::default()
+            },
+
+            Self::HomeReadOnly => Sandbox {
+                preset: Some(*self),
+                isolate_home: false,
+
+                mounts: Mounts {
+                    read_only: home.into_iter()
+                        .map(|home| (home.clone(), home))
+                        .collect(),
+
+                    ..Mounts::default()
+                },
+
+                ..Sandbox::default()
+            },
+
+            Self::NetworkTools => Sandbox {
+                preset: Some(*self),
+                isolate_home: true,
+
+                // Missing configs are skipped so they don't fail the validation
+                mounts: Mounts {
+                    read_only: home.map(|home| {
+                        NETWORK_TOOLS_CONFIGS.iter()
+                            .map(|config| format!("{home}/{config}"))
+                            .filter(|config| Path::new(config).exists())
+                            .map(|config| (config.clone(), config))
+                            .collect()
+                    }).unwrap_or_default(),
+
+                    ..Mounts::default()
+                },
+
+                ..Sandbox::default()
+            }
+        }
+    }
+}
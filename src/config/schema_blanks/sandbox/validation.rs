@@ -0,0 +1,84 @@
+use std::fmt::Display;
+use std::path::Path;
+
+use super::Sandbox;
+use super::paths::{normalize, is_inside};
+
+/// Folder with the paths mounted by the launcher itself
+pub const RESERVED_FOLDER: &str = "/tmp/sandbox";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Mistake in the user-given sandbox settings
+pub enum SandboxProblem {
+    /// Mounted host path doesn't exist
+    MissingSource {
+        source: String,
+        target: String
+    },
+
+    /// Two paths are mounted to the same place, or one of them
+    /// is mounted inside of another one
+    OverlappingTargets(String, String),
+
+    /// Path is mounted to the folder used by the launcher
+    ReservedTarget(String)
+}
+
+impl Display for SandboxProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingSource { source, target } => write!(f, "{source} mounted to {target} doesn't exist"),
+            Self::OverlappingTargets(a, b) => write!(f, "sandbox paths {a} and {b} overlap"),
+            Self::ReservedTarget(target) => write!(f, "sandbox path {target} is placed inside of {RESERVED_FOLDER}")
+        }
+    }
+}
+
+/// Find mistakes in the mounts and private paths of the sandbox
+///
+/// Every problem is reported only once, in a stable order
+pub fn get_problems(sandbox: &Sandbox) -> Vec<SandboxProblem> {
+    let mut problems = Vec::new();
+
+    let mut sources = sandbox.mounts.read_only.iter()
+        .chain(sandbox.mounts.bind.iter())
+        .map(|(source, target)| (source.trim(), normalize(target)))
+        .collect::<Vec<_>>();
+
+    sources.sort();
+
+    for (source, target) in sources {
+        if !Path::new(source).exists() {
+            problems.push(SandboxProblem::MissingSource {
+                source: source.to_string(),
+                target
+            });
+        }
+    }
+
+    let mut targets = sandbox.mounts.read_only.values()
+        .chain(sandbox.mounts.bind.values())
+        .chain(sandbox.mounts.symlinks.values())
+        .chain(sandbox.private.iter())
+        .map(|target| normalize(target))
+        .filter(|target| !target.is_empty())
+        .collect::<Vec<_>>();
+
+    targets.sort();
+
+    for (i, target) in targets.iter().enumerate() {
+        if is_inside(target, RESERVED_FOLDER) && (i == 0 || &targets[i - 1] != target) {
+            problems.push(SandboxProblem::ReservedTarget(target.clone()));
+        }
+
+        // Mounts from the config are not ordered, so nested mounts
+        // could hide each other in a random way
+        for other in &targets[i + 1..] {
+            if is_inside(other, target) {
+                problems.push(SandboxProblem::OverlappingTargets(target.clone(), other.clone()));
+            }
+        }
+    }
+
+    problems
+}
@@ -3,6 +3,8 @@ pub mod wine_sync;
 pub mod wine_drives;
 pub mod virtual_desktop;
 pub mod shared_libraries;
+pub mod umu;
+pub mod steam_runtime;
 
 pub mod prelude {
     pub use super::wine_drives::*;
@@ -11,6 +13,8 @@ pub mod prelude {
     pub use super::wine_sync::WineSync;
     pub use super::virtual_desktop::VirtualDesktop;
     pub use super::shared_libraries::SharedLibraries;
+    pub use super::umu::Umu;
+    pub use super::steam_runtime::SteamRuntime;
 }
 
 #[macro_export]
@@ -28,7 +32,12 @@ macro_rules! config_impl_wine_schema {
             pub virtual_desktop: VirtualDesktop,
             pub shared_libraries: SharedLibraries,
             pub timeout_fix: bool,
-            pub winewayland: bool
+            pub winewayland: bool,
+
+            /// Used when the selected build is a Proton build
+            pub umu: Umu,
+
+            pub steam_runtime: SteamRuntime
         }
 
         impl Default for Wine {
@@ -47,7 +56,9 @@ macro_rules! config_impl_wine_schema {
                     virtual_desktop: VirtualDesktop::default(),
                     shared_libraries: SharedLibraries::default(),
                     timeout_fix: false,
-                    winewayland: false
+                    winewayland: false,
+                    umu: Umu::default(),
+                    steam_runtime: SteamRuntime::default()
                 }
             }
         }
@@ -112,6 +123,14 @@ macro_rules! config_impl_wine_schema {
                     winewayland: value.get("winewayland")
                         .and_then(|value| value.as_bool())
                         .unwrap_or(default.winewayland),
+
+                    umu: value.get("umu")
+                        .map(Umu::from)
+                        .unwrap_or(default.umu),
+
+                    steam_runtime: value.get("steam_runtime")
+                        .map(SteamRuntime::from)
+                        .unwrap_or(default.steam_runtime)
                 }
             }
         }
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Serialize, Deserialize};
+use serde_json::Value as JsonValue;
+
+/// Names of the runtime folders, newest first
+const RUNTIME_FOLDERS: &[&str] = &[
+    "SteamLinuxRuntime_sniper",
+    "SteamLinuxRuntime_soldier"
+];
+
+/// Steam installations relative to the home folder
+const STEAM_FOLDERS: &[&str] = &[
+    ".local/share/Steam",
+    ".steam/steam",
+    ".steam/root",
+    ".var/app/com.valvesoftware.Steam/.local/share/Steam"
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// Run wine inside of the Steam Linux Runtime container (pressure-vessel)
+///
+/// Can't be used with Proton builds, the sandbox,
+/// and background commands like FPS unlocker
+///
+/// https://gitlab.steamos.cloud/steamrt/steam-runtime-tools/-/blob/main/docs/container-runtime.md
+pub struct SteamRuntime {
+    pub enabled: bool,
+
+    /// Path to the runtime folder, e.g. `SteamLinuxRuntime_sniper`.
+    /// If not set, the runtime is searched in the Steam library
+    pub path: Option<PathBuf>
+}
+
+impl From<&JsonValue> for SteamRuntime {
+    fn from(value: &JsonValue) -> Self {
+        let default = Self::default();
+
+        Self {
+            enabled: value.get("enabled")
+                .and_then(JsonValue::as_bool)
+                .unwrap_or(default.enabled),
+
+            path: match value.get("path") {
+                Some(value) => value.as_str()
+                    .filter(|path| !path.is_empty())
+                    .map(PathBuf::from),
+
+                None => default.path
+            }
+        }
+    }
+}
+
+/// Get path to the runtime entry point in the given folder
+///
+/// `_v2-entry-point` is preferred over the `run` script
+pub fn get_entry_point(folder: impl AsRef<Path>) -> Option<PathBuf> {
+    ["_v2-entry-point", "run"].into_iter()
+        .map(|name| folder.as_ref().join(name))
+        .find(|path| path.is_file())
+}
+
+/// Find installed Steam Linux Runtime in the Steam library
+pub fn find_runtime() -> Option<PathBuf> {
+    let home = PathBuf::from(std::env::var("HOME").ok()?);
+
+    STEAM_FOLDERS.iter()
+        .map(|steam| home.join(steam).join("steamapps/common"))
+        .flat_map(|library| RUNTIME_FOLDERS.iter().map(move |runtime| library.join(runtime)))
+        .find(|folder| get_entry_point(folder).is_some())
+}
+
+impl SteamRuntime {
+    /// Get path to the runtime folder
+    ///
+    /// Returns configured path, or the runtime found in the Steam library
+    pub fn get_path(&self) -> Option<PathBuf> {
+        match &self.path {
+            Some(path) => Some(path.clone()),
+            None => find_runtime()
+        }
+    }
+
+    /// Get runtime command which wraps the wine command
+    ///
+    /// The wrapped command must be separated by `--`.
+    /// Returns `None` if the runtime is disabled
+    pub fn get_command(&self) -> anyhow::Result<Option<Vec<String>>> {
+        if !self.enabled {
+            return Ok(None);
+        }
+
+        let Some(path) = self.get_path()
+        else {
+            anyhow::bail!("Couldn't find installed Steam Linux Runtime");
+        };
+
+        let Some(entry_point) = get_entry_point(&path)
+        else {
+            anyhow::bail!("Steam Linux Runtime entry point not found in {path:?}");
+        };
+
+        let mut command = vec![entry_point.to_string_lossy().to_string()];
+
+        if entry_point.ends_with("_v2-entry-point") {
+            command.push(String::from("--verb=waitforexitandrun"));
+        }
+
+        Ok(Some(command))
+    }
+
+    /// Get environment variables which expose given folders to the runtime container
+    ///
+    /// Returns empty map if the runtime is disabled
+    ///
+    /// ```
+    /// use anime_launcher_sdk::config::schema_blanks::prelude::SteamRuntime;
+    ///
+    /// let runtime = SteamRuntime {
+    ///     enabled: true,
+    ///     path: None
+    /// };
+    ///
+    /// let env = runtime.get_env_vars(["/games/game", "/games/prefix"]);
+    ///
+    /// assert_eq!(env["PRESSURE_VESSEL_FILESYSTEMS_RW"], "/games/game:/games/prefix");
+    /// assert!(SteamRuntime::default().get_env_vars(["/games/game"]).is_empty());
+    /// ```
+    pub fn get_env_vars<T: AsRef<Path>>(&self, folders: impl IntoIterator<Item = T>) -> HashMap<&str, String> {
+        let mut env = HashMap::new();
+
+        if self.enabled {
+            let folders = folders.into_iter()
+                .map(|folder| folder.as_ref().to_string_lossy().to_string())
+                .collect::<Vec<_>>();
+
+            env.insert("PRESSURE_VESSEL_FILESYSTEMS_RW", folders.join(":"));
+        }
+
+        env
+    }
+}
@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+use serde::{Serialize, Deserialize};
+use serde_json::Value as JsonValue;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Settings of the umu-launcher used to run Proton builds
+///
+/// https://github.com/Open-Wine-Components/umu-launcher
+pub struct Umu {
+    /// Name or path of the `umu-run` binary. Default is `umu-run`
+    pub binary: String,
+
+    /// Game id from the umu database used to apply protonfixes.
+    /// If not set, the game's default id is used
+    pub game_id: Option<String>
+}
+
+impl Default for Umu {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            binary: String::from("umu-run"),
+            game_id: None
+        }
+    }
+}
+
+impl From<&JsonValue> for Umu {
+    fn from(value: &JsonValue) -> Self {
+        let default = Self::default();
+
+        Self {
+            binary: value.get("binary")
+                .and_then(JsonValue::as_str)
+                .map(|binary| binary.trim())
+                .filter(|binary| !binary.is_empty())
+                .map(String::from)
+                .unwrap_or(default.binary),
+
+            game_id: match value.get("game_id") {
+                Some(value) => value.as_str()
+                    .filter(|id| !id.is_empty())
+                    .map(String::from),
+
+                None => default.game_id
+            }
+        }
+    }
+}
+
+impl Umu {
+    /// Get folder where umu-run keeps the steam runtime
+    pub fn get_data_folder() -> Option<PathBuf> {
+        std::env::var("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|_| std::env::var("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+            .map(|folder| folder.join("umu"))
+            .ok()
+    }
+}
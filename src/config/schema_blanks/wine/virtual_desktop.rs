@@ -2,6 +2,7 @@ use serde::{Serialize, Deserialize};
 use serde_json::Value as JsonValue;
 
 use crate::config::schema_blanks::resolution::Resolution;
+use crate::shell;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct VirtualDesktop {
@@ -50,8 +51,17 @@ impl VirtualDesktop {
     #[inline]
     /// `explorer /desktop=[desktop_name],[width]x[height]`
     pub fn get_command<T: AsRef<str>>(&self, desktop_name: T) -> Option<String> {
+        self.get_args(desktop_name).map(shell::join)
+    }
+
+    #[inline]
+    /// `["explorer", "/desktop=[desktop_name],[width]x[height]"]`
+    pub fn get_args<T: AsRef<str>>(&self, desktop_name: T) -> Option<Vec<String>> {
         if self.enabled {
-            Some(format!("explorer /desktop={},{}x{}", desktop_name.as_ref(), self.width, self.height))
+            Some(vec![
+                String::from("explorer"),
+                format!("/desktop={},{}x{}", desktop_name.as_ref(), self.width, self.height)
+            ])
         }
 
         else {
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+use serde_json::Value as JsonValue;
+
+use enum_ordinalize::Ordinalize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Ordinalize, Serialize, Deserialize)]
+/// Place of the wrapper in the launch command
+///
+/// ```text
+/// <sandbox> -- <outer wrappers> <gamescope> -- <taskset/nice/ionice> <gamemoderun> <inner wrappers> <steam runtime> -- <wine>
+/// ```
+///
+/// Wrappers are always started inside of the sandbox
+pub enum WrapperPosition {
+    /// Wrap gamescope and everything started inside of it, e.g. `prime-run`
+    Outer,
+
+    /// Wrap only the game runner, e.g. `mangohud --dlsym` or `obs-gamecapture`
+    Inner
+}
+
+impl Default for WrapperPosition {
+    #[inline]
+    fn default() -> Self {
+        Self::Inner
+    }
+}
+
+impl From<&JsonValue> for WrapperPosition {
+    #[inline]
+    fn from(value: &JsonValue) -> Self {
+        serde_json::from_value(value.clone()).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// User-given command which wraps the game launch command
+///
+/// Keywords like `%prefix%` and `%game%` are replaced in the arguments
+/// and environment values the same way as in the game launch command.
+/// Unknown keywords are kept as is in the environment values
+pub struct CustomWrapper {
+    pub enabled: bool,
+
+    /// Wrapper binary, e.g. `prime-run` or `strace`
+    pub binary: String,
+
+    pub args: Vec<String>,
+    pub position: WrapperPosition,
+
+    /// Environment variables set for the game when the wrapper is enabled
+    pub env: HashMap<String, String>
+}
+
+impl Default for CustomWrapper {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            binary: String::new(),
+            args: Vec::new(),
+            position: WrapperPosition::default(),
+            env: HashMap::new()
+        }
+    }
+}
+
+impl CustomWrapper {
+    #[inline]
+    /// Get wrapper binary followed by its arguments
+    pub fn get_command(&self) -> Vec<String> {
+        std::iter::once(self.binary.clone())
+            .chain(self.args.iter().cloned())
+            .collect()
+    }
+}
+
+impl From<&JsonValue> for CustomWrapper {
+    fn from(value: &JsonValue) -> Self {
+        let default = Self::default();
+
+        Self {
+            enabled: value.get("enabled")
+                .and_then(JsonValue::as_bool)
+                .unwrap_or(default.enabled),
+
+            binary: value.get("binary")
+                .and_then(JsonValue::as_str)
+                .map(|binary| binary.trim().to_string())
+                .unwrap_or(default.binary),
+
+            args: match value.get("args").and_then(JsonValue::as_array) {
+                Some(values) => values.iter()
+                    .filter_map(JsonValue::as_str)
+                    .map(String::from)
+                    .collect(),
+
+                None => default.args
+            },
+
+            position: value.get("position")
+                .map(WrapperPosition::from)
+                .unwrap_or(default.position),
+
+            env: match value.get("env").and_then(JsonValue::as_object) {
+                Some(values) => values.iter()
+                    .filter_map(|(key, value)| value.as_str().map(|value| (key.clone(), value.to_string())))
+                    .collect(),
+
+                None => default.env
+            }
+        }
+    }
+}
+
+/// Get list of wrappers from the config value
+///
+/// Wrappers without binary are skipped
+///
+/// ```
+/// use anime_launcher_sdk::config::schema_blanks::wrappers::*;
+///
+/// let wrappers = get_wrappers(&serde_json::json!([
+///     { "binary": "prime-run", "position": "Outer" },
+///     { "binary": "mangohud", "args": ["--dlsym"], "env": { "MANGOHUD_CONFIG": "fps" } },
+///     { "binary": "" }
+/// ]));
+///
+/// assert_eq!(wrappers.len(), 2);
+/// assert_eq!(wrappers[0].position, WrapperPosition::Outer);
+/// assert_eq!(wrappers[1].get_command(), ["mangohud", "--dlsym"]);
+/// assert_eq!(wrappers[1].position, WrapperPosition::Inner);
+/// assert!(wrappers[1].enabled);
+/// ```
+pub fn get_wrappers(value: &JsonValue) -> Vec<CustomWrapper> {
+    value.as_array()
+        .map(|wrappers| wrappers.iter()
+            .map(CustomWrapper::from)
+            .filter(|wrapper| !wrapper.binary.is_empty())
+            .collect())
+        .unwrap_or_default()
+}
@@ -2,6 +2,7 @@ use serde::{Serialize, Deserialize};
 use serde_json::Value as JsonValue;
 
 use crate::config::schema_blanks::prelude::*;
+use crate::config::schema_blanks::wrappers::get_wrappers;
 
 #[cfg(feature = "fps-unlocker")]
 use super::FpsUnlocker;
@@ -15,7 +16,16 @@ pub struct Enhancements {
     #[cfg(feature = "fps-unlocker")]
     pub fps_unlocker: FpsUnlocker,
 
-    pub gamescope: Gamescope
+    pub gamescope: Gamescope,
+
+    /// Additional wrappers of the launch command
+    pub wrappers: Vec<CustomWrapper>,
+
+    /// CPU cores and priorities of the game process
+    pub cpu: CpuScheduling,
+
+    /// Prevent screen locking and suspend while the game is running
+    pub inhibit: SleepInhibit
 }
 
 impl From<&JsonValue> for Enhancements {
@@ -42,7 +52,19 @@ impl From<&JsonValue> for Enhancements {
 
             gamescope: value.get("gamescope")
                 .map(Gamescope::from)
-                .unwrap_or(default.gamescope)
+                .unwrap_or(default.gamescope),
+
+            wrappers: value.get("wrappers")
+                .map(get_wrappers)
+                .unwrap_or(default.wrappers),
+
+            cpu: value.get("cpu")
+                .map(CpuScheduling::from)
+                .unwrap_or(default.cpu),
+
+            inhibit: value.get("inhibit")
+                .map(SleepInhibit::from)
+                .unwrap_or(default.inhibit)
         }
     }
 }
@@ -39,8 +39,17 @@ pub struct Game {
     pub wine: Wine,
     pub dxvk: Dxvk,
     pub enhancements: Enhancements,
+
+    /// Environment variables of the game. Keywords like `%prefix%` are replaced
+    /// in the values. Names ending with `+`, e.g. `LD_LIBRARY_PATH+`, append
+    /// the value to the one set by the launcher or by the host system
     pub environment: HashMap<String, String>,
-    pub command: Option<String>
+
+    /// Template of the launch command, e.g. `gamemoderun %command%`.
+    /// Keywords like `%game%` are replaced in it, and `%%` is a literal `%`
+    pub command: Option<String>,
+    pub hooks: GameHooks,
+    pub limits: ResourceLimits
 }
 
 impl Default for Game {
@@ -55,7 +64,9 @@ impl Default for Game {
             dxvk: Dxvk::default(),
             enhancements: Enhancements::default(),
             environment: HashMap::new(),
-            command: None
+            command: None,
+            hooks: GameHooks::default(),
+            limits: ResourceLimits::default()
         }
     }
 }
@@ -129,7 +140,15 @@ impl From<&JsonValue> for Game {
                     }
                 },
                 None => default.command
-            }
+            },
+
+            hooks: value.get("hooks")
+                .map(GameHooks::from)
+                .unwrap_or(default.hooks),
+
+            limits: value.get("limits")
+                .map(ResourceLimits::from)
+                .unwrap_or(default.limits)
         }
     }
 }
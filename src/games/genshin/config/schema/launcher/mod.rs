@@ -52,6 +52,8 @@ pub struct Launcher {
     pub temp: Option<PathBuf>,
     pub sophon: SophonConfig,
     pub repairer: Repairer,
+    pub logs: GameLogs,
+    pub playtime_limits: PlaytimeLimits,
 
     #[cfg(feature = "environment-emulation")]
     pub environment: Environment,
@@ -73,6 +75,8 @@ impl Default for Launcher {
             temp: launcher_dir().ok(),
             sophon: SophonConfig::default(),
             repairer: Repairer::default(),
+            logs: GameLogs::default(),
+            playtime_limits: PlaytimeLimits::default(),
 
             #[cfg(feature = "environment-emulation")]
             environment: Environment::default(),
@@ -139,6 +143,14 @@ impl From<&JsonValue> for Launcher {
                 None => default.repairer
             },
 
+            logs: value.get("logs")
+                .map(GameLogs::from)
+                .unwrap_or(default.logs),
+
+            playtime_limits: value.get("playtime_limits")
+                .map(PlaytimeLimits::from)
+                .unwrap_or(default.playtime_limits),
+
             #[cfg(feature = "environment-emulation")]
             environment: match value.get("environment") {
                 Some(value) => serde_json::from_value(value.clone()).unwrap_or(default.environment),
@@ -7,7 +7,8 @@ use crate::config::schema_blanks::sandbox::Sandbox;
 #[cfg(feature = "components")]
 use crate::components::{
     wine::Version as WineVersion,
-    dxvk::Version as DxvkVersion
+    dxvk::Version as DxvkVersion,
+    runner::Runner
 };
 
 pub mod launcher;
@@ -79,6 +80,15 @@ impl Schema {
         }
     }
 
+    #[cfg(feature = "components")]
+    /// Get selected runner: wine or proton build
+    pub fn get_selected_runner(&self) -> anyhow::Result<Option<Runner>> {
+        match &self.game.wine.selected {
+            Some(selected) => Runner::find_in(&self.components.path, &self.game.wine.builds, selected),
+            None => Ok(None)
+        }
+    }
+
     #[cfg(feature = "components")]
     /// Get selected dxvk version
     pub fn get_selected_dxvk(&self) -> anyhow::Result<Option<DxvkVersion>> {
@@ -2,6 +2,9 @@ use std::path::PathBuf;
 
 pub const FOLDER_NAME: &str = "anime-game-launcher";
 
+/// Game id in the umu database used when the game is started with Proton
+pub const UMU_GAME_ID: &str = "umu-genshin";
+
 lazy_static::lazy_static! {
     // Limit max amount of log data in a file
     // This is needed to stop wine from flushing
@@ -78,6 +81,20 @@ pub fn cache_dir() -> anyhow::Result<PathBuf> {
     path.canonicalize().or(Ok(path))
 }
 
+/// Get game sessions logs folder path
+///
+/// Default is `$HOME/.local/share/anime-game-launcher/logs`
+pub fn logs_dir() -> anyhow::Result<PathBuf> {
+    launcher_dir().map(|dir| dir.join("logs"))
+}
+
+/// Get playtime history file path
+///
+/// Default is `$HOME/.local/share/anime-game-launcher/playtime.jsonl`
+pub fn playtime_file() -> anyhow::Result<PathBuf> {
+    launcher_dir().map(|dir| dir.join("playtime.jsonl"))
+}
+
 /// Get config file path
 ///
 /// Default is `$HOME/.local/share/anime-game-launcher/config.json`
@@ -0,0 +1,84 @@
+use std::path::Path;
+
+use serde_json::{json, Value as JsonValue};
+
+use anime_game_core::prelude::*;
+use anime_game_core::genshin::prelude::*;
+
+use crate::config::ConfigExt;
+use crate::genshin::config::Config;
+use crate::genshin::consts;
+use crate::launch::logs::SessionLogs;
+use crate::diagnostics::{self, DiagnosticsBundle};
+
+#[cfg(feature = "states")]
+use crate::genshin::states::LauncherState;
+
+/// Write `.tar.gz` archive with the info needed for bug reports
+///
+/// Archive contains the launcher config, latest game logs, selected
+/// components, system info and the launcher state. Home folder paths
+/// and secret values are redacted
+#[tracing::instrument(level = "info", ret)]
+pub fn export_diagnostics(path: impl AsRef<Path> + std::fmt::Debug) -> anyhow::Result<()> {
+    tracing::info!("Exporting diagnostics");
+
+    let config = Config::get()?;
+
+    let home = std::env::var("HOME").ok();
+    let home = home.as_deref();
+
+    let mut bundle = DiagnosticsBundle::default();
+
+    let mut config_json = serde_json::to_value(&config)?;
+
+    diagnostics::redact_json(&mut config_json, home);
+
+    bundle.add_json("config.json", &config_json)?;
+    bundle.add_logs(&SessionLogs::new(consts::logs_dir()?), diagnostics::LOGS_AMOUNT, home)?;
+
+    let game_path = config.game.path.for_edition(config.launcher.edition);
+
+    let game_version = Game::new(game_path, config.launcher.edition)
+        .get_version()
+        .map(|version| version.to_string());
+
+    let wine = match config.get_selected_runner() {
+        Ok(Some(runner)) => json!({
+            "name": runner.name(),
+            "features": diagnostics::to_report(runner.features(&config.components.path))
+        }),
+
+        Ok(None) => JsonValue::Null,
+        Err(err) => json!({ "error": err.to_string() })
+    };
+
+    let dxvk = config.get_selected_dxvk()
+        .map(|dxvk| dxvk.map(|dxvk| dxvk.version));
+
+    let mut report = json!({
+        "system": diagnostics::get_system_report(),
+        "edition": serde_json::to_value(config.launcher.edition)?,
+        "game_version": diagnostics::to_report(game_version),
+        "wine": wine,
+        "dxvk": diagnostics::to_report(dxvk),
+        "gamescope": serde_json::to_value(&config.game.enhancements.gamescope)?,
+        "hud": serde_json::to_value(config.game.enhancements.hud)?
+    });
+
+    #[cfg(feature = "sandbox")]
+    {
+        report["sandbox"] = serde_json::to_value(&config.sandbox)?;
+    }
+
+    #[cfg(feature = "states")]
+    {
+        report["state"] = diagnostics::to_report(LauncherState::get_from_config(|_| {}).map(|state| format!("{state:?}")));
+    }
+
+    diagnostics::redact_json(&mut report, home);
+
+    bundle.add_json("report.json", &report)?;
+
+    bundle.write(path)
+}
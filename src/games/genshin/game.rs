@@ -1,505 +1,235 @@
-use std::io::{Read, Write};
-use std::process::{Command, Stdio};
-use std::path::PathBuf;
-use std::fs::File;
-use std::sync::{Arc, Mutex};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
 use anime_game_core::prelude::*;
 use anime_game_core::genshin::telemetry;
 use anime_game_core::genshin::game::Game;
 
 use crate::config::ConfigExt;
-use crate::genshin::config::Config;
-use crate::config::schema_blanks::prelude::{AllowedDrives, WineDrives};
+use crate::genshin::config::{Config, Schema};
 use crate::genshin::consts;
+use crate::launch::logs::SessionLogs;
+use crate::launch::playtime::{PlaytimeHistory, PlaytimeAllowance, Clock};
+use crate::launch::analyzer::{self, Finding, Signature};
+use crate::launch::{GameHandle, GameSpecific, LaunchPlan, LaunchSteps, ResolvedLaunch, RunOutcome};
 #[cfg(feature = "fps-unlocker")]
 use super::fps_unlocker::FpsUnlocker;
 #[cfg(feature = "sessions")]
-use crate::{genshin::sessions::Sessions, sessions::SessionsExt};
-
-#[derive(Debug, Clone)]
-struct Folders {
-    pub wine: PathBuf,
-    pub prefix: PathBuf,
-    pub game: PathBuf,
-    pub temp: PathBuf
-}
-
-fn replace_keywords(command: impl ToString, folders: &Folders) -> String {
-    command
-        .to_string()
-        .replace("%build%", folders.wine.to_str().unwrap())
-        .replace("%prefix%", folders.prefix.to_str().unwrap())
-        .replace("%temp%", folders.game.to_str().unwrap())
-        .replace(
-            "%launcher%",
-            &consts::launcher_dir().unwrap().to_string_lossy()
-        )
-        .replace("%game%", folders.temp.to_str().unwrap())
-}
+use crate::{genshin::sessions::Sessions, launch::GameSessions};
 
-/// Try to run the game
+/// Known failures of the game which can be found in its log
 ///
-/// This function will freeze thread it was called from while the game is
-/// running
+/// Log excerpts matched by every signature are stored in `tests/fixtures/logs/genshin`
 ///
-/// Returns `true` if driverError.log was created during a short-lived session.
-#[tracing::instrument(level = "info", ret)]
-pub fn run() -> anyhow::Result<bool> {
-    tracing::info!("Preparing to run the game");
-
-    let config = Config::get()?;
-
-    let game_executable = match config.launcher.edition {
+/// ```
+/// use anime_launcher_sdk::launch::analyzer::{analyze, Finding};
+/// use anime_launcher_sdk::genshin::game::LOG_SIGNATURES;
+///
+/// let log = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/logs/genshin/anti-cheat.log"));
+///
+/// assert!(matches!(&analyze(log, LOG_SIGNATURES)[0], Finding::KnownFailure { signature, .. } if signature.id == "anti-cheat"));
+/// ```
+pub const LOG_SIGNATURES: &[Signature] = &[
+    Signature {
+        id: "anti-cheat",
+        title: "Anti-cheat driver has rejected the game",
+        hint: "Use the wine build recommended by the launcher",
+        patterns: &["ZwLoadDriver", "HoYoKProtect"]
+    }
+];
+
+/// Get game specific launch inputs from the given config
+fn get_game_specific(config: &Schema) -> anyhow::Result<GameSpecific> {
+    let executable = match config.launcher.edition {
         genshin::GameEdition::Global => "GenshinImpact.exe",
         genshin::GameEdition::China => "YuanShen.exe"
     };
 
     let game_path = config.game.path.for_edition(config.launcher.edition);
 
-    if !game_path.exists() {
-        return Err(anyhow::anyhow!("Game is not installed"));
-    }
-
-    let Some(wine) = config.get_selected_wine()?
-    else {
-        anyhow::bail!("Couldn't find wine executable");
-    };
-
-    let features = wine.features(&config.components.path)?.unwrap_or_default();
-
-    let mut folders = Folders {
-        wine: config.game.wine.builds.join(&wine.name),
-        prefix: config.game.wine.prefix.clone(),
-        game: config
-            .game
-            .path
-            .for_edition(config.launcher.edition)
-            .to_path_buf(),
-        temp: config.launcher.temp.clone().unwrap_or(std::env::temp_dir())
+    let edition = serde_json::to_value(config.launcher.edition)?
+        .as_str()
+        .map(String::from)
+        .unwrap_or_default();
+
+    let game_version = Game::new(game_path, config.launcher.edition)
+        .get_version()
+        .map(|version| version.to_string())
+        .unwrap_or_default();
+
+    let game = GameSpecific {
+        executable: executable.to_string(),
+        windows_command: vec![executable.to_string()],
+        virtual_desktop: String::from("an_anime_game"),
+        timeout_fix: config.game.wine.timeout_fix,
+        winewayland: config.game.wine.winewayland,
+        edition,
+        game_version,
+        telemetry_servers: anime_game_core::genshin::consts::telemetry_servers(config.launcher.edition)
+            .into_iter()
+            .map(|server| server.to_string())
+            .collect(),
+        umu_game_id: consts::UMU_GAME_ID.to_string(),
+        folder_name: consts::FOLDER_NAME.to_string(),
+        launcher_dir: consts::launcher_dir()?,
+        ..GameSpecific::default()
     };
 
-    // Check telemetry servers (skipped when the user opted out of
-    // automatic telemetry disabling)
-
-    if config.launcher.disable_telemetry {
-        tracing::info!("Checking telemetry");
-
-        if let Ok(Some(server)) = telemetry::is_disabled(config.launcher.edition) {
-            return Err(anyhow::anyhow!(
-                "Telemetry server is not disabled: {server}"
-            ));
-        }
-    }
-
-    else {
-        tracing::info!("Telemetry check is disabled in the launcher settings");
-    }
-
-    // Prepare fps unlocker
-    // 1) Download if needed
-    // 2) Construct the fps unlock invocation and set some vars for use later
-
-    let mut fps_unlocker_exe_command = None::<String>;
-    let mut fps_unlocker_dir = None::<String>;
-
+    // FPS unlocker is started in background together with the game
     #[cfg(feature = "fps-unlocker")]
-    if config.game.enhancements.fps_unlocker.enabled {
-        tracing::info!("Preparing FPS unlocker");
-
-        let unlocker = match FpsUnlocker::from_dir(&config.game.enhancements.fps_unlocker.path) {
-            Ok(Some(unlocker)) => unlocker,
-
-            other => {
-                // Ok(None) means unknown version, so we should delete it before downloading
-                // newer one because otherwise downloader will try to continue
-                // downloading "partially downloaded" file
-                if let Ok(None) = other {
-                    std::fs::remove_file(FpsUnlocker::get_binary_in(
-                        &config.game.enhancements.fps_unlocker.path
-                    ))?;
-                }
-
-                tracing::info!("Unlocker is not downloaded. Downloading");
-
-                match FpsUnlocker::download(&config.game.enhancements.fps_unlocker.path) {
-                    Ok(unlocker) => unlocker,
-                    Err(err) => {
-                        return Err(anyhow::anyhow!("Failed to download FPS unlocker: {err}"));
-                    }
-                }
-            }
-        };
-
-        let unlocker_config = &config.game.enhancements.fps_unlocker.config;
-        fps_unlocker_exe_command = Some(format!(
-            "{}/fpsunlock.exe {} {}",
-            unlocker.dir().to_string_lossy(),
-            unlocker_config.fps,
-            unlocker_config.interval
-        ));
-        fps_unlocker_dir = Some(unlocker.dir().to_string_lossy().into_owned());
-    }
-
-    // Generate `config.ini` if environment emulation feature is presented
-
-    #[cfg(feature = "environment-emulation")]
-    {
-        let game = Game::new(game_path, config.launcher.edition);
-
-        std::fs::write(
-            game_path.join("config.ini"),
-            config
-                .launcher
-                .environment
-                .generate_config(game.get_version()?.to_string())
-        )?;
-    }
-
-    config
-        .game
-        .wine
-        .drives
-        .map_folders(&folders.game, &config.game.wine.prefix)?;
-
-    // Workaround for sandboxing feature
-    if config.sandbox.enabled {
-        WineDrives::map_folder(&config.game.wine.prefix, AllowedDrives::C, "../drive_c")?;
-        WineDrives::map_folder(&config.game.wine.prefix, AllowedDrives::Z, "/")?;
-    }
-
-    // Prepare bash -c '<command>'
-    // %command% = %bash_command% %windows_command% %launch_args%
-
-    let mut bash_command = String::new();
-    let mut windows_command = String::new();
-    let mut launch_args = String::new();
-
-    if config.game.enhancements.gamemode {
-        bash_command += "gamemoderun ";
-    }
-
-    let run_command = features
-        .command
-        .map(|command| replace_keywords(command, &folders))
-        .unwrap_or(format!(
-            "'{}'",
-            folders
-                .wine
-                .join(wine.files.wine64.unwrap_or(wine.files.wine))
-                .to_string_lossy()
-        ));
-
-    bash_command += &run_command;
-    bash_command += " ";
-
-    if let Some(virtual_desktop) = config
-        .game
-        .wine
-        .virtual_desktop
-        .get_command("an_anime_game")
-    {
-        windows_command += &virtual_desktop;
-        windows_command += " ";
-    }
-
-    windows_command += game_executable;
-    windows_command += " ";
-
-    if config.game.wine.borderless {
-        launch_args += "-screen-fullscreen 0 -popupwindow ";
-    }
-
-    // https://notabug.org/Krock/dawn/src/master/TWEAKS.md
-    if config.game.enhancements.fsr.enabled {
-        launch_args += "-window-mode exclusive ";
-    }
-
-    // gamescope <params> -- <command to run>
-    if let Some(gamescope) = config.game.enhancements.gamescope.get_command() {
-        bash_command = format!("{gamescope} -- {bash_command}");
-    }
-
-    #[cfg(feature = "fps-unlocker")]
-    if config.game.enhancements.fps_unlocker.enabled {
-        if let Some(fps_unlocker_exe_command) = fps_unlocker_exe_command {
-            bash_command = format!("{run_command} {fps_unlocker_exe_command} & {bash_command}");
+    let game = {
+        let mut game = game;
+        let unlocker = &config.game.enhancements.fps_unlocker;
+
+        if unlocker.enabled {
+            game.background.push(vec![
+                format!("{}/fpsunlock.exe", unlocker.path.to_string_lossy()),
+                unlocker.config.fps.to_string(),
+                unlocker.config.interval.to_string()
+            ]);
+
+            game.mounts.push((unlocker.path.clone(), String::from("/tmp/sandbox/fps-unlocker")));
         }
-    }
 
-    // Finalize launching command
-    bash_command = match &config.game.command {
-        // Use user-given launch command
-        Some(command) => replace_keywords(command, &folders)
-            .replace(
-                "%command%",
-                &format!("{bash_command} {windows_command} {launch_args}")
-            )
-            .replace("%bash_command%", &bash_command)
-            .replace("%windows_command%", &windows_command)
-            .replace("%launch_args%", &launch_args),
-
-        // Combine bash and windows parts of the command
-        None => format!("{bash_command} {windows_command} {launch_args}")
+        game
     };
 
-    // bwrap <params> -- <command to run>
-    #[cfg(feature = "sandbox")]
-    if config.sandbox.enabled {
-        let mut bwrap = config.sandbox.get_command(
-            folders.wine.to_str().unwrap(),
-            folders.prefix.to_str().unwrap(),
-            folders.game.to_str().unwrap()
-        );
-
-        let sandboxed_folders = Folders {
-            wine: PathBuf::from("/tmp/sandbox/wine"),
-            prefix: PathBuf::from("/tmp/sandbox/prefix"),
-            game: PathBuf::from("/tmp/sandbox/game"),
-            temp: PathBuf::from("/tmp")
-        };
-
-        #[cfg(feature = "fps-unlocker")]
-        if config.game.enhancements.fps_unlocker.enabled {
-            if let Some(fps_unlocker_dir) = fps_unlocker_dir {
-                bwrap += &format!(" --bind {fps_unlocker_dir} /tmp/sandbox/fps-unlocker");
-                bash_command = bash_command.replace(&fps_unlocker_dir, "/tmp/sandbox/fps-unlocker");
-            }
-        }
+    Ok(game)
+}
 
-        bash_command = bash_command
-            .replace(
-                folders.wine.to_str().unwrap(),
-                sandboxed_folders.wine.to_str().unwrap()
-            )
-            .replace(
-                folders.prefix.to_str().unwrap(),
-                sandboxed_folders.prefix.to_str().unwrap()
-            )
-            .replace(
-                folders.game.to_str().unwrap(),
-                sandboxed_folders.game.to_str().unwrap()
-            )
-            .replace(
-                folders.temp.to_str().unwrap(),
-                sandboxed_folders.temp.to_str().unwrap()
-            );
-
-        bash_command = format!("{bwrap} --chdir /tmp/sandbox/game -- bash -c \"{bash_command}\"");
-        folders = sandboxed_folders;
+/// Download FPS unlocker if it's enabled
+#[cfg(feature = "fps-unlocker")]
+fn prepare_fps_unlocker(config: &Schema) -> anyhow::Result<()> {
+    if !config.game.enhancements.fps_unlocker.enabled {
+        return Ok(());
     }
 
-    // delete driverError.log so we can detect if timeout fix is needed
-    let driver_error_log = game_path.join("driverError.log");
-    let _ = std::fs::remove_file(&driver_error_log);
-
-    let launch_time = std::time::Instant::now();
-
-    let mut command = Command::new("bash");
+    tracing::info!("Preparing FPS unlocker");
 
-    command.arg("-c");
-    command.arg(&bash_command);
+    match FpsUnlocker::from_dir(&config.game.enhancements.fps_unlocker.path) {
+        Ok(Some(_)) => Ok(()),
 
-    // Setup environment
-
-    command.env("WINEARCH", "win64");
-    command.env("WINEPREFIX", &folders.prefix);
+        other => {
+            // Ok(None) means unknown version, so we should delete it before downloading
+            // newer one because otherwise downloader will try to continue
+            // downloading "partially downloaded" file
+            if let Ok(None) = other {
+                std::fs::remove_file(FpsUnlocker::get_binary_in(&config.game.enhancements.fps_unlocker.path))?;
+            }
 
-    // Add environment flags for selected wine
-    for (key, value) in features.env.into_iter() {
-        command.env(key, replace_keywords(value, &folders));
-    }
+            tracing::info!("Unlocker is not downloaded. Downloading");
 
-    // Add environment flags for selected dxvk
-    if let Ok(Some(dxvk)) = config.get_selected_dxvk() {
-        if let Ok(Some(features)) = dxvk.features(&config.components.path) {
-            for (key, value) in features.env.iter() {
-                command.env(key, replace_keywords(value, &folders));
+            match FpsUnlocker::download(&config.game.enhancements.fps_unlocker.path) {
+                Ok(_) => Ok(()),
+                Err(err) => Err(anyhow::anyhow!("Failed to download FPS unlocker: {err}"))
             }
         }
     }
+}
 
-    let wine_folder = folders.wine.clone();
-
-    command.envs(
-        config
-            .game
-            .enhancements
-            .hud
-            .get_env_vars(config.game.enhancements.gamescope.enabled)
-    );
-    command.envs(config.game.enhancements.fsr.get_env_vars());
-
-    command.envs(config.game.wine.sync.get_env_vars());
-    command.envs(config.game.wine.language.get_env_vars());
-    command.envs(config.game.wine.shared_libraries.get_env_vars(wine_folder));
-
-    if config.game.wine.timeout_fix {
-        command.env("WINE_ENABLE_TIMEOUT_FIX", "1");
-    }
-
-    if config.game.wine.winewayland {
-        command.env("DISPLAY", "");
-    }
-
-    command.envs(&config.game.environment);
-
-    #[cfg(feature = "sessions")]
-    if let Some(current) = Sessions::get_current()? {
-        Sessions::apply(current, &config.game.wine.prefix)?;
-    }
+/// Build the game launch plan from the given config
+///
+/// This function has no side effects: it doesn't touch the wine prefix
+/// nor the game folder
+pub fn get_launch_plan(config: &Schema) -> anyhow::Result<LaunchPlan> {
+    LaunchPlan::from_config(&crate::launch_config!(config), get_game_specific(config)?)
+}
 
-    // Run command
-
-    let variables = command
-        .get_envs()
-        .map(|(key, value)| {
-            format!(
-                "{}=\"{}\"",
-                key.to_string_lossy(),
-                value.unwrap_or_default().to_string_lossy()
-            )
-        })
-        .fold(String::new(), |acc, env| acc + " " + &env);
-
-    tracing::info!("Running the game with command: {variables} bash -c \"{bash_command}\"");
-
-    // We use real current dir here because sandboxed one
-    // obviously doesn't exist
-    let mut child = command
-        .current_dir(config.game.path.for_edition(config.launcher.edition))
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()?;
-
-    // Create new game.log file to log all the game output
-    let game_output = Arc::new(Mutex::new(File::create(
-        consts::launcher_dir()?.join("game.log")
-    )?));
-
-    let written = Arc::new(AtomicUsize::new(0));
-
-    let mut stdout_join = None;
-    let mut stderr_join = None;
-
-    // Redirect stdout to the game.log file
-    if let Some(mut stdout) = child.stdout.take() {
-        let game_output = game_output.clone();
-        let written = written.clone();
-
-        stdout_join = Some(std::thread::spawn(move || -> std::io::Result<()> {
-            let mut buf = [0; 1024];
-
-            while let Ok(read) = stdout.read(&mut buf) {
-                if read == 0 {
-                    break;
-                }
-
-                let Ok(mut game_output) = game_output.lock()
-                else {
-                    break;
-                };
-
-                for line in buf[..read].split(|c| c == &b'\n') {
-                    game_output.write_all(b"    [stdout] ")?;
-                    game_output.write_all(line)?;
-                    game_output.write_all(b"\n")?;
-
-                    written.fetch_add(line.len() + 14, Ordering::Relaxed);
-                }
-
-                if written.load(Ordering::Relaxed) > *consts::GAME_LOG_FILE_LIMIT {
-                    break;
-                }
-            }
+/// Get the command, environment and sandbox mounts
+/// which `run()` would use without running the game
+///
+/// Unlike `run()` this function doesn't touch the wine prefix
+/// nor the game folder
+#[tracing::instrument(level = "info", ret)]
+pub fn run_dry() -> anyhow::Result<ResolvedLaunch> {
+    let config = Config::get()?;
 
-            Ok(())
-        }));
-    }
+    get_launch_plan(&config)?.resolve()
+}
 
-    // Redirect stderr to the game.log file
-    if let Some(mut stderr) = child.stderr.take() {
-        let game_output = game_output.clone();
-        let written = written.clone();
+/// Start the game without blocking the current thread
+///
+/// Post-exit steps like sessions update are run by the returned handle
+/// when the game is closed
+#[tracing::instrument(level = "info", ret)]
+pub fn launch() -> anyhow::Result<GameHandle> {
+    tracing::info!("Preparing to run the game");
 
-        stderr_join = Some(std::thread::spawn(move || -> std::io::Result<()> {
-            let mut buf = [0; 1024];
+    let config = Config::get()?;
 
-            while let Ok(read) = stderr.read(&mut buf) {
-                if read == 0 {
-                    break;
-                }
+    let launch_config = crate::launch_config!(config);
+    let plan = LaunchPlan::from_config(&launch_config, get_game_specific(&config)?)?;
 
-                let Ok(mut game_output) = game_output.lock()
-                else {
-                    break;
-                };
+    crate::launch::launch_game(plan, &launch_config, LaunchSteps {
+        telemetry: Box::new(|| telemetry::is_disabled(config.launcher.edition).ok().flatten()),
 
-                for line in buf[..read].split(|c| c == &b'\n') {
-                    game_output.write_all(b"[!] [stderr] ")?;
-                    game_output.write_all(line)?;
-                    game_output.write_all(b"\n")?;
+        prepare: Some(Box::new(|| {
+            #[cfg(feature = "fps-unlocker")]
+            prepare_fps_unlocker(&config)?;
 
-                    written.fetch_add(line.len() + 14, Ordering::Relaxed);
-                }
+            // Generate `config.ini` if environment emulation feature is presented
+            #[cfg(feature = "environment-emulation")]
+            {
+                let game = Game::new(&launch_config.game, config.launcher.edition);
 
-                if written.load(Ordering::Relaxed) > *consts::GAME_LOG_FILE_LIMIT {
-                    break;
-                }
+                std::fs::write(
+                    launch_config.game.join("config.ini"),
+                    config.launcher.environment.generate_config(game.get_version()?.to_string())
+                )?;
             }
 
             Ok(())
-        }));
-    }
-
-    child.wait()?;
-
-    // Flush and close the game log file
-    if let Ok(mut file) = game_output.lock() {
-        file.flush()?;
-    }
+        })),
+
+        map_root_drive: false,
+        timeout_fix: Some(Duration::from_secs(20)),
+        logs_dir: consts::logs_dir()?,
+        game_log: consts::launcher_dir()?.join("game.log"),
+        log_limit: *consts::GAME_LOG_FILE_LIMIT,
+        playtime_file: consts::playtime_file()?,
+
+        #[cfg(feature = "sessions")]
+        sessions: GameSessions::of::<Sessions>()
+    })
+}
 
-    drop(game_output);
+/// Try to run the game
+///
+/// This function will freeze thread it was called from while the game is
+/// running
+#[tracing::instrument(level = "info", ret)]
+pub fn run() -> anyhow::Result<RunOutcome> {
+    launch()?.wait()
+}
 
-    if let Some(join) = stdout_join {
-        join.join()
-            .map_err(|err| anyhow::anyhow!("Failed to join stdout reader thread: {err:?}"))??;
-    }
+/// Analyze log of the latest game session
+///
+/// Returns `None` if the game was never launched
+pub fn analyze_latest_log() -> anyhow::Result<Option<Vec<Finding>>> {
+    let Some(log) = SessionLogs::new(consts::logs_dir()?).latest()?
+    else {
+        return Ok(None);
+    };
 
-    if let Some(join) = stderr_join {
-        join.join()
-            .map_err(|err| anyhow::anyhow!("Failed to join stderr reader thread: {err:?}"))??;
-    }
+    Ok(Some(analyzer::analyze_session(&log, LOG_SIGNATURES)?))
+}
 
-    // Workaround for fast process closing (is it still a thing?)
-    loop {
-        std::thread::sleep(std::time::Duration::from_secs(3));
-
-        let output = Command::new("ps")
-            .arg("-A")
-            .stdout(Stdio::piped())
-            .output()?;
-        let output = String::from_utf8_lossy(&output.stdout);
-
-        if !output.contains("GenshinImpact.e")
-            && !output.contains("YuanShen.exe")
-            && !output.contains("fpsunlock.exe")
-        {
-            break;
-        }
-    }
+/// Get history of the game sessions
+///
+/// Sessions of all the game editions are returned,
+/// use `PlaytimeHistory::edition` to filter them
+pub fn get_playtime_history() -> anyhow::Result<PlaytimeHistory> {
+    PlaytimeHistory::load(consts::playtime_file()?)
+}
 
+/// Get time the game can be played now according to the playtime limits
+///
+/// Budget is chosen for the currently selected `Sessions` account
+pub fn get_playtime_allowance(config: &Schema, clock: &impl Clock) -> anyhow::Result<PlaytimeAllowance> {
     #[cfg(feature = "sessions")]
-    if let Some(current) = Sessions::get_current()? {
-        Sessions::update(current, &config.game.wine.prefix)?;
-    }
+    let account = Sessions::get_current()?;
+
+    #[cfg(not(feature = "sessions"))]
+    let account: Option<String> = None;
 
-    // suggest enabling the timeout fix if:
-    // - not already enabled
-    // - game exited within 20 seconds
-    // - driverError.log was recreated during the session
-    Ok(!config.game.wine.timeout_fix
-        && launch_time.elapsed().as_secs() < 20
-        && driver_error_log.exists())
+    get_playtime_history()?.get_allowance(&config.launcher.playtime_limits, account.as_deref(), clock)
 }
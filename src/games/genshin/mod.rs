@@ -15,5 +15,8 @@ pub mod fps_unlocker;
 #[cfg(feature = "game")]
 pub mod game;
 
+#[cfg(feature = "game")]
+pub mod diagnostics;
+
 #[cfg(feature = "sessions")]
 pub mod sessions;
@@ -6,6 +6,15 @@ use anime_game_core::genshin::prelude::*;
 use crate::config::ConfigExt;
 use crate::genshin::config::Config;
 
+#[cfg(feature = "components")]
+use crate::components::runner::RunnerKind;
+
+#[cfg(feature = "sandbox")]
+use crate::config::schema_blanks::sandbox::SandboxBackend;
+
+#[cfg(feature = "game")]
+use crate::launch::playtime::{PlaytimeAllowance, SystemClock};
+
 #[derive(Debug, Clone)]
 pub enum LauncherState {
     Launch,
@@ -27,6 +36,21 @@ pub enum LauncherState {
     #[cfg(feature = "components")]
     WineNotInstalled,
 
+    /// Selected Proton build is started through the `umu-run`
+    /// which is not installed
+    #[cfg(feature = "components")]
+    UmuNotInstalled,
+
+    /// Selected sandbox backend is not installed
+    /// or can't be used on the current system
+    #[cfg(feature = "sandbox")]
+    SandboxNotAvailable(SandboxBackend),
+
+    /// Game can't be played now because of the playtime limits.
+    /// Never contains allowed playtime
+    #[cfg(feature = "game")]
+    PlaytimeNotAllowed(PlaytimeAllowance),
+
     PrefixNotExists,
 
     DxvkNotInstalled,
@@ -66,6 +90,7 @@ pub struct LauncherStateParams<F: Fn(StateUpdating)> {
 
     /// When `false`, the launcher will not check nor automatically disable
     /// game telemetry servers (treats telemetry as already disabled).
+    /// `get_from_config` also sets it to `false` when the servers are blocked in the sandbox.
     pub disable_telemetry: bool,
 
     pub status_updater: F
@@ -200,6 +225,21 @@ impl LauncherState {
             _ => ()
         }
 
+        // Proton builds can't be started without the umu-run
+        #[cfg(feature = "components")]
+        if let Some(selected) = &config.game.wine.selected {
+            let kind = RunnerKind::detect(config.game.wine.builds.join(selected));
+
+            if kind == Some(RunnerKind::Proton) && crate::find_binary(&config.game.wine.umu.binary).is_none() {
+                return Ok(Self::UmuNotInstalled);
+            }
+        }
+
+        #[cfg(feature = "sandbox")]
+        if config.sandbox.is_enabled() && !config.sandbox.backend.is_available() {
+            return Ok(Self::SandboxNotAvailable(config.sandbox.backend));
+        }
+
         let mut voices = Vec::with_capacity(config.game.voices.len());
 
         for voice in &config.game.voices {
@@ -209,16 +249,34 @@ impl LauncherState {
             });
         }
 
-        Self::get(LauncherStateParams {
+        // Telemetry servers blocked in the sandbox don't need to be checked
+        #[cfg(feature = "sandbox")]
+        let check_telemetry = config.launcher.disable_telemetry && !config.sandbox.blocks_telemetry();
+
+        #[cfg(not(feature = "sandbox"))]
+        let check_telemetry = config.launcher.disable_telemetry;
+
+        #[cfg(feature = "game")]
+        let allowance = crate::genshin::game::get_playtime_allowance(&config, &SystemClock)?;
+
+        let state = Self::get(LauncherStateParams {
             game_path: config.game.path.for_edition(config.launcher.edition).to_path_buf(),
             game_edition: config.launcher.edition,
 
             wine_prefix: config.game.wine.prefix,
             selected_voices: voices,
 
-            disable_telemetry: config.launcher.disable_telemetry,
+            disable_telemetry: check_telemetry,
 
             status_updater
-        })
+        })?;
+
+        // Playtime limits don't prevent the game from being updated
+        #[cfg(feature = "game")]
+        if matches!(state, Self::Launch | Self::PredownloadAvailable { .. }) && !allowance.is_allowed() {
+            return Ok(Self::PlaytimeNotAllowed(allowance));
+        }
+
+        Ok(state)
     }
 }
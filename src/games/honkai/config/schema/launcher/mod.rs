@@ -47,6 +47,8 @@ pub struct Launcher {
     pub temp: Option<PathBuf>,
     pub sophon: SophonConfig,
     pub repairer: Repairer,
+    pub logs: GameLogs,
+    pub playtime_limits: PlaytimeLimits,
     pub disable_telemetry: bool,
     pub behavior: LauncherBehavior
 }
@@ -61,6 +63,8 @@ impl Default for Launcher {
             temp: launcher_dir().ok(),
             sophon: SophonConfig::default(),
             repairer: Repairer::default(),
+            logs: GameLogs::default(),
+            playtime_limits: PlaytimeLimits::default(),
             disable_telemetry: true,
             behavior: LauncherBehavior::default()
         }
@@ -112,6 +116,14 @@ impl From<&JsonValue> for Launcher {
                 None => default.repairer
             },
 
+            logs: value.get("logs")
+                .map(GameLogs::from)
+                .unwrap_or(default.logs),
+
+            playtime_limits: value.get("playtime_limits")
+                .map(PlaytimeLimits::from)
+                .unwrap_or(default.playtime_limits),
+
             disable_telemetry: match value.get("disable_telemetry") {
                 Some(value) => serde_json::from_value(value.to_owned()).unwrap_or(default.disable_telemetry),
                 None => default.disable_telemetry
@@ -4,7 +4,7 @@ use serde_json::Value as JsonValue;
 #[cfg(feature = "sandbox")]
 use crate::config::schema_blanks::sandbox::Sandbox;
 #[cfg(feature = "components")]
-use crate::components::{dxvk::Version as DxvkVersion, wine::Version as WineVersion};
+use crate::components::{dxvk::Version as DxvkVersion, runner::Runner, wine::Version as WineVersion};
 
 pub mod launcher;
 pub mod game;
@@ -83,6 +83,15 @@ impl Schema {
         }
     }
 
+    #[cfg(feature = "components")]
+    /// Get selected runner: wine or proton build
+    pub fn get_selected_runner(&self) -> anyhow::Result<Option<Runner>> {
+        match &self.game.wine.selected {
+            Some(selected) => Runner::find_in(&self.components.path, &self.game.wine.builds, selected),
+            None => Ok(None)
+        }
+    }
+
     #[cfg(feature = "components")]
     /// Get selected dxvk version
     pub fn get_selected_dxvk(&self) -> anyhow::Result<Option<DxvkVersion>> {
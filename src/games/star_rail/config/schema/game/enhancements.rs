@@ -2,13 +2,23 @@ use serde::{Serialize, Deserialize};
 use serde_json::Value as JsonValue;
 
 use crate::config::schema_blanks::prelude::*;
+use crate::config::schema_blanks::wrappers::get_wrappers;
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Enhancements {
     pub fsr: Fsr,
     pub gamemode: bool,
     pub hud: HUD,
-    pub gamescope: Gamescope
+    pub gamescope: Gamescope,
+
+    /// Additional wrappers of the launch command
+    pub wrappers: Vec<CustomWrapper>,
+
+    /// CPU cores and priorities of the game process
+    pub cpu: CpuScheduling,
+
+    /// Prevent screen locking and suspend while the game is running
+    pub inhibit: SleepInhibit
 }
 
 impl From<&JsonValue> for Enhancements {
@@ -30,7 +40,19 @@ impl From<&JsonValue> for Enhancements {
 
             gamescope: value.get("gamescope")
                 .map(Gamescope::from)
-                .unwrap_or(default.gamescope)
+                .unwrap_or(default.gamescope),
+
+            wrappers: value.get("wrappers")
+                .map(get_wrappers)
+                .unwrap_or(default.wrappers),
+
+            cpu: value.get("cpu")
+                .map(CpuScheduling::from)
+                .unwrap_or(default.cpu),
+
+            inhibit: value.get("inhibit")
+                .map(SleepInhibit::from)
+                .unwrap_or(default.inhibit)
         }
     }
 }
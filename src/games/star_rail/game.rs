@@ -1,361 +1,180 @@
-use std::io::{Read, Write};
-use std::process::{Command, Stdio};
-use std::path::PathBuf;
-use std::fs::File;
-use std::sync::{Arc, Mutex};
-use std::sync::atomic::{AtomicUsize, Ordering};
-
+use anime_game_core::prelude::*;
 use anime_game_core::star_rail::telemetry;
+use anime_game_core::star_rail::game::Game;
 
 use crate::config::ConfigExt;
-use crate::star_rail::config::Config;
-
-use crate::config::schema_blanks::prelude::{
-    WineDrives,
-    AllowedDrives
-};
-
+use crate::star_rail::config::{Config, Schema};
 use crate::star_rail::consts;
+use crate::launch::logs::SessionLogs;
+use crate::launch::playtime::{PlaytimeHistory, PlaytimeAllowance, Clock};
+use crate::launch::analyzer::{self, Finding, Signature};
+use crate::launch::{GameHandle, GameSpecific, LaunchPlan, LaunchSteps, ResolvedLaunch, RunOutcome};
 
 #[cfg(feature = "sessions")]
 use crate::{
-    sessions::SessionsExt,
+    launch::GameSessions,
     star_rail::sessions::Sessions
 };
 
-#[derive(Debug, Clone)]
-struct Folders {
-    pub wine: PathBuf,
-    pub prefix: PathBuf,
-    pub game: PathBuf,
-    pub patch: PathBuf,
-    pub temp: PathBuf
+/// Known failures of the game which can be found in its log
+///
+/// Log excerpts matched by every signature are stored in `tests/fixtures/logs/star-rail`
+///
+/// ```
+/// use anime_launcher_sdk::launch::analyzer::{analyze, Finding};
+/// use anime_launcher_sdk::star_rail::game::LOG_SIGNATURES;
+///
+/// let log = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/logs/star-rail/anti-cheat.log"));
+///
+/// assert!(matches!(&analyze(log, LOG_SIGNATURES)[0], Finding::KnownFailure { signature, .. } if signature.id == "anti-cheat"));
+/// ```
+///
+/// ```
+/// use anime_launcher_sdk::launch::analyzer::{analyze, Finding};
+/// use anime_launcher_sdk::star_rail::game::LOG_SIGNATURES;
+///
+/// let log = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/logs/star-rail/jadeite.log"));
+///
+/// assert!(matches!(&analyze(log, LOG_SIGNATURES)[0], Finding::KnownFailure { signature, .. } if signature.id == "jadeite"));
+/// ```
+pub const LOG_SIGNATURES: &[Signature] = &[
+    Signature {
+        id: "anti-cheat",
+        title: "Anti-cheat driver has rejected the game",
+        hint: "Update the game patch, or wait until it's updated for the current game version",
+        patterns: &["ZwLoadDriver", "mhyprot"]
+    },
+    Signature {
+        id: "jadeite",
+        title: "Jadeite has refused to start the game",
+        hint: "Update the game patch, or wait until it's updated for the current game version",
+        patterns: &["jadeite", "not supported"]
+    }
+];
+
+/// Get game specific launch inputs from the given config
+fn get_game_specific(config: &Schema) -> anyhow::Result<GameSpecific> {
+    let game_path = config.game.path.for_edition(config.launcher.edition);
+
+    let edition = serde_json::to_value(config.launcher.edition)?
+        .as_str()
+        .map(String::from)
+        .unwrap_or_default();
+
+    let game_version = Game::new(game_path, config.launcher.edition)
+        .get_version()
+        .map(|version| version.to_string())
+        .unwrap_or_default();
+
+    // We run the jadeite patch from the Z: drive
+    Ok(GameSpecific {
+        executable: String::from("StarRail.exe"),
+        windows_command: vec![
+            format!("{}/jadeite.exe", config.patch.path.to_string_lossy()),
+            format!("Z:\\{}/StarRail.exe", game_path.to_string_lossy()),
+            String::from("--")
+        ],
+        patch: Some(config.patch.path.clone()),
+        virtual_desktop: String::from("star_rail"),
+        winewayland: config.game.wine.winewayland,
+        edition,
+        game_version,
+        telemetry_servers: anime_game_core::star_rail::consts::telemetry_servers(config.launcher.edition)
+            .into_iter()
+            .map(|server| server.to_string())
+            .collect(),
+        umu_game_id: consts::UMU_GAME_ID.to_string(),
+        folder_name: consts::FOLDER_NAME.to_string(),
+        launcher_dir: consts::launcher_dir()?,
+        ..GameSpecific::default()
+    })
 }
 
-fn replace_keywords(command: impl ToString, folders: &Folders) -> String {
-    command.to_string()
-        .replace("%build%", folders.wine.to_str().unwrap())
-        .replace("%prefix%", folders.prefix.to_str().unwrap())
-        .replace("%temp%", folders.game.to_str().unwrap())
-        .replace("%launcher%", &consts::launcher_dir().unwrap().to_string_lossy())
-        .replace("%game%", folders.temp.to_str().unwrap())
-        .replace("%patch%", folders.patch.to_str().unwrap())
+/// Build the game launch plan from the given config
+///
+/// This function has no side effects: it doesn't touch the wine prefix
+/// nor the game folder
+pub fn get_launch_plan(config: &Schema) -> anyhow::Result<LaunchPlan> {
+    LaunchPlan::from_config(&crate::launch_config!(config), get_game_specific(config)?)
 }
 
-/// Try to run the game
+/// Get the command, environment and sandbox mounts
+/// which `run()` would use without running the game
 ///
-/// This function will freeze thread it was called from while the game is running
+/// Unlike `run()` this function doesn't touch the wine prefix
+/// nor the game folder
 #[tracing::instrument(level = "info", ret)]
-pub fn run() -> anyhow::Result<()> {
-    tracing::info!("Preparing to run the game");
-
+pub fn run_dry() -> anyhow::Result<ResolvedLaunch> {
     let config = Config::get()?;
-    let game_path = config.game.path.for_edition(config.launcher.edition).to_path_buf();
-
-    if !game_path.exists() {
-        return Err(anyhow::anyhow!("Game is not installed"));
-    }
-
-    let Some(wine) = config.get_selected_wine()? else {
-        anyhow::bail!("Couldn't find wine executable");
-    };
 
-    let features = wine.features(&config.components.path)?.unwrap_or_default();
+    get_launch_plan(&config)?.resolve()
+}
 
-    let mut folders = Folders {
-        wine: config.game.wine.builds.join(&wine.name),
-        prefix: config.game.wine.prefix.clone(),
-        game: game_path.clone(),
-        patch: config.patch.path.clone(),
-        temp: config.launcher.temp.clone().unwrap_or(std::env::temp_dir())
-    };
+/// Start the game without blocking the current thread
+///
+/// Post-exit steps like sessions update are run by the returned handle
+/// when the game is closed
+#[tracing::instrument(level = "info", ret)]
+pub fn launch() -> anyhow::Result<GameHandle> {
+    tracing::info!("Preparing to run the game");
 
-    // Check telemetry servers (skipped when the user opted out of
-    // automatic telemetry disabling)
+    let config = Config::get()?;
 
-    if config.launcher.disable_telemetry {
-        tracing::info!("Checking telemetry");
+    let launch_config = crate::launch_config!(config);
+    let plan = LaunchPlan::from_config(&launch_config, get_game_specific(&config)?)?;
+
+    crate::launch::launch_game(plan, &launch_config, LaunchSteps {
+        telemetry: Box::new(|| telemetry::is_disabled(config.launcher.edition).ok().flatten()),
+        prepare: None,
+        map_root_drive: true,
+        timeout_fix: None,
+        logs_dir: consts::logs_dir()?,
+        game_log: consts::launcher_dir()?.join("game.log"),
+        log_limit: *consts::GAME_LOG_FILE_LIMIT,
+        playtime_file: consts::playtime_file()?,
+
+        #[cfg(feature = "sessions")]
+        sessions: GameSessions::of::<Sessions>()
+    })
+}
 
-        if let Ok(Some(server)) = telemetry::is_disabled(config.launcher.edition) {
-            return Err(anyhow::anyhow!("Telemetry server is not disabled: {server}"));
-        }
-    }
+/// Try to run the game
+///
+/// This function will freeze thread it was called from while the game is running
+#[tracing::instrument(level = "info", ret)]
+pub fn run() -> anyhow::Result<RunOutcome> {
+    launch()?.wait()
+}
 
+/// Analyze log of the latest game session
+///
+/// Returns `None` if the game was never launched
+pub fn analyze_latest_log() -> anyhow::Result<Option<Vec<Finding>>> {
+    let Some(log) = SessionLogs::new(consts::logs_dir()?).latest()?
     else {
-        tracing::info!("Telemetry check is disabled in the launcher settings");
-    }
-
-    // Prepare wine prefix drives
-    config.game.wine.drives.map_folders(&folders.game, &config.game.wine.prefix)?;
-
-    // Workaround for the jadeite patch (we run it from Z: drive)
-    WineDrives::map_folder(&config.game.wine.prefix, AllowedDrives::Z, "/")?;
-
-    // Workaround for sandboxing feature
-    if config.sandbox.enabled {
-        WineDrives::map_folder(&config.game.wine.prefix, AllowedDrives::C, "../drive_c")?;
-    }
-
-    // Prepare bash -c '<command>'
-    // %command% = %bash_command% %windows_command% %launch_args%
-
-    let mut bash_command = String::new();
-    let mut windows_command = String::new();
-    let mut launch_args = String::new();
-
-    if config.game.enhancements.gamemode {
-        bash_command += "gamemoderun ";
-    }
-
-    let run_command = features.command
-        .map(|command| replace_keywords(command, &folders))
-        .unwrap_or(format!("'{}'", folders.wine.join(wine.files.wine64.unwrap_or(wine.files.wine)).to_string_lossy()));
-
-    bash_command += &run_command;
-    bash_command += " ";
-
-    if let Some(virtual_desktop) = config.game.wine.virtual_desktop.get_command("star_rail") {
-        windows_command += &virtual_desktop;
-        windows_command += " ";
-    }
-
-    windows_command += &format!("'{}/jadeite.exe' 'Z:\\{}/StarRail.exe' -- ", folders.patch.to_string_lossy(), folders.game.to_string_lossy());
-
-    if config.game.wine.borderless {
-        launch_args += "-screen-fullscreen 0 -popupwindow ";
-    }
-
-    // https://notabug.org/Krock/dawn/src/master/TWEAKS.md
-    if config.game.enhancements.fsr.enabled {
-        launch_args += "-window-mode exclusive ";
-    }
-
-    // gamescope <params> -- <command to run>
-    if let Some(gamescope) = config.game.enhancements.gamescope.get_command() {
-        bash_command = format!("{gamescope} -- {bash_command}");
-    }
-
-    // bwrap <params> -- <command to run>
-    #[cfg(feature = "sandbox")]
-    if config.sandbox.enabled {
-        let bwrap = config.sandbox.get_command(
-            folders.wine.to_str().unwrap(),
-            folders.prefix.to_str().unwrap(),
-            folders.game.to_str().unwrap()
-        );
-
-        let bwrap = format!("{bwrap} --bind '{}' /tmp/sandbox/patch", folders.patch.to_string_lossy());
-
-        let sandboxed_folders = Folders {
-            wine: PathBuf::from("/tmp/sandbox/wine"),
-            prefix: PathBuf::from("/tmp/sandbox/prefix"),
-            game: PathBuf::from("/tmp/sandbox/game"),
-            patch: PathBuf::from("/tmp/sandbox/patch"),
-            temp: PathBuf::from("/tmp")
-        };
-
-        bash_command = bash_command
-            .replace(folders.wine.to_str().unwrap(), sandboxed_folders.wine.to_str().unwrap())
-            .replace(folders.prefix.to_str().unwrap(), sandboxed_folders.prefix.to_str().unwrap())
-            .replace(folders.game.to_str().unwrap(), sandboxed_folders.game.to_str().unwrap())
-            .replace(folders.patch.to_str().unwrap(), sandboxed_folders.patch.to_str().unwrap())
-            .replace(folders.temp.to_str().unwrap(), sandboxed_folders.temp.to_str().unwrap());
-
-        windows_command = windows_command
-            .replace(folders.wine.to_str().unwrap(), sandboxed_folders.wine.to_str().unwrap())
-            .replace(folders.prefix.to_str().unwrap(), sandboxed_folders.prefix.to_str().unwrap())
-            .replace(folders.game.to_str().unwrap(), sandboxed_folders.game.to_str().unwrap())
-            .replace(folders.patch.to_str().unwrap(), sandboxed_folders.patch.to_str().unwrap())
-            .replace(folders.temp.to_str().unwrap(), sandboxed_folders.temp.to_str().unwrap());
-
-        bash_command = format!("{bwrap} --chdir /tmp/sandbox/game -- {bash_command}");
-        folders = sandboxed_folders;
-    }
-
-    // Finalize launching command
-    bash_command = match &config.game.command {
-        // Use user-given launch command
-        Some(command) => replace_keywords(command, &folders)
-            .replace("%command%", &format!("{bash_command} {windows_command}"))
-            .replace("%bash_command%", &bash_command)
-            .replace("%windows_command%", &windows_command)
-            .replace("%launch_args%", &launch_args),
-
-        // Combine bash and windows parts of the command
-        None => format!("{bash_command} {windows_command} {launch_args}")
+        return Ok(None);
     };
 
-    let mut command = Command::new("bash");
-
-    command.arg("-c");
-    command.arg(&bash_command);
-
-    // Setup environment
-
-    command.env("WINEARCH", "win64");
-    command.env("WINEPREFIX", &folders.prefix);
-
-    // Add environment flags for selected wine
-    for (key, value) in features.env.into_iter() {
-        command.env(key, replace_keywords(value, &folders));
-    }
-
-    // Add environment flags for selected dxvk
-    if let Ok(Some(dxvk)) = config.get_selected_dxvk() {
-        if let Ok(Some(features)) = dxvk.features(&config.components.path) {
-            for (key, value) in features.env.iter() {
-                command.env(key, replace_keywords(value, &folders));
-            }
-        }
-    }
-
-    let wine_folder = folders.wine.clone();
-
-    command.envs(config.game.enhancements.hud.get_env_vars(config.game.enhancements.gamescope.enabled));
-    command.envs(config.game.enhancements.fsr.get_env_vars());
-
-    command.envs(config.game.wine.sync.get_env_vars());
-    command.envs(config.game.wine.language.get_env_vars());
-    command.envs(config.game.wine.shared_libraries.get_env_vars(wine_folder));
-
-    if config.game.wine.winewayland {
-        command.env("DISPLAY", "");
-    }
+    Ok(Some(analyzer::analyze_session(&log, LOG_SIGNATURES)?))
+}
 
-    command.envs(&config.game.environment);
+/// Get history of the game sessions
+///
+/// Sessions of all the game editions are returned,
+/// use `PlaytimeHistory::edition` to filter them
+pub fn get_playtime_history() -> anyhow::Result<PlaytimeHistory> {
+    PlaytimeHistory::load(consts::playtime_file()?)
+}
 
+/// Get time the game can be played now according to the playtime limits
+///
+/// Budget is chosen for the currently selected `Sessions` account
+pub fn get_playtime_allowance(config: &Schema, clock: &impl Clock) -> anyhow::Result<PlaytimeAllowance> {
     #[cfg(feature = "sessions")]
-    if let Some(current) = Sessions::get_current()? {
-        Sessions::apply(current, &config.game.wine.prefix)?;
-    }
-
-    // Run command
-
-    let variables = command
-        .get_envs()
-        .map(|(key, value)| format!("{}=\"{}\"", key.to_string_lossy(), value.unwrap_or_default().to_string_lossy()))
-        .fold(String::new(), |acc, env| acc + " " + &env);
-
-    tracing::info!("Running the game with command: {variables} bash -c \"{bash_command}\"");
-
-    // We use real current dir here because sandboxed one
-    // obviously doesn't exist
-    let mut child = command.current_dir(config.game.path.for_edition(config.launcher.edition))
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()?;
-
-    // Create new game.log file to log all the game output
-    let game_output = Arc::new(Mutex::new(
-        File::create(consts::launcher_dir()?.join("game.log"))?
-    ));
-
-    let written = Arc::new(AtomicUsize::new(0));
-
-    let mut stdout_join = None;
-    let mut stderr_join = None;
-
-    // Redirect stdout to the game.log file
-    if let Some(mut stdout) = child.stdout.take() {
-        let game_output = game_output.clone();
-        let written = written.clone();
-
-        stdout_join = Some(std::thread::spawn(move || -> std::io::Result<()> {
-            let mut buf = [0; 1024];
-
-            while let Ok(read) = stdout.read(&mut buf) {
-                if read == 0 {
-                    break;
-                }
+    let account = Sessions::get_current()?;
 
-                let Ok(mut game_output) = game_output.lock() else {
-                    break;
-                };
-
-                for line in buf[..read].split(|c| c == &b'\n') {
-                    game_output.write_all(b"    [stdout] ")?;
-                    game_output.write_all(line)?;
-                    game_output.write_all(b"\n")?;
-
-                    written.fetch_add(line.len() + 14, Ordering::Relaxed);
-                }
-
-                if written.load(Ordering::Relaxed) > *consts::GAME_LOG_FILE_LIMIT {
-                    break;
-                }
-            }
-
-            Ok(())
-        }));
-    }
-
-    // Redirect stderr to the game.log file
-    if let Some(mut stderr) = child.stderr.take() {
-        let game_output = game_output.clone();
-        let written = written.clone();
-
-        stderr_join = Some(std::thread::spawn(move || -> std::io::Result<()> {
-            let mut buf = [0; 1024];
-
-            while let Ok(read) = stderr.read(&mut buf) {
-                if read == 0 {
-                    break;
-                }
-
-                let Ok(mut game_output) = game_output.lock() else {
-                    break;
-                };
-
-                for line in buf[..read].split(|c| c == &b'\n') {
-                    game_output.write_all(b"[!] [stderr] ")?;
-                    game_output.write_all(line)?;
-                    game_output.write_all(b"\n")?;
-
-                    written.fetch_add(line.len() + 14, Ordering::Relaxed);
-                }
-
-                if written.load(Ordering::Relaxed) > *consts::GAME_LOG_FILE_LIMIT {
-                    break;
-                }
-            }
-
-            Ok(())
-        }));
-    }
-
-    child.wait()?;
-
-    // Flush and close the game log file
-    if let Ok(mut file) = game_output.lock() {
-        file.flush()?;
-    }
-
-    drop(game_output);
-
-    if let Some(join) = stdout_join {
-        join.join().map_err(|err| anyhow::anyhow!("Failed to join stdout reader thread: {err:?}"))??;
-    }
-
-    if let Some(join) = stderr_join {
-        join.join().map_err(|err| anyhow::anyhow!("Failed to join stderr reader thread: {err:?}"))??;
-    }
-
-    // Workaround for fast process closing (is it still a thing?)
-    loop {
-        std::thread::sleep(std::time::Duration::from_secs(3));
-
-        let output = Command::new("ps").arg("-A").stdout(Stdio::piped()).output()?;
-        let output = String::from_utf8_lossy(&output.stdout);
-
-        if !output.contains("StarRail.exe") {
-            break;
-        }
-    }
-
-    #[cfg(feature = "sessions")]
-    if let Some(current) = Sessions::get_current()? {
-        Sessions::update(current, &config.game.wine.prefix)?;
-    }
+    #[cfg(not(feature = "sessions"))]
+    let account: Option<String> = None;
 
-    Ok(())
+    get_playtime_history()?.get_allowance(&config.launcher.playtime_limits, account.as_deref(), clock)
 }
@@ -9,5 +9,8 @@ pub mod states;
 #[cfg(feature = "game")]
 pub mod game;
 
+#[cfg(feature = "game")]
+pub mod diagnostics;
+
 #[cfg(feature = "sessions")]
 pub mod sessions;
@@ -57,6 +57,8 @@ pub struct Launcher {
     pub background_index: u8,
     pub temp: Option<PathBuf>,
     pub repairer: Repairer,
+    pub logs: GameLogs,
+    pub playtime_limits: PlaytimeLimits,
 
     #[cfg(feature = "environment-emulation")]
     pub environment: Environment,
@@ -74,6 +76,8 @@ impl Default for Launcher {
             video_background: true,
             temp: launcher_dir().ok(),
             repairer: Repairer::default(),
+            logs: GameLogs::default(),
+            playtime_limits: PlaytimeLimits::default(),
             background_index: 0,
 
             #[cfg(feature = "discord-rpc")]
@@ -138,6 +142,14 @@ impl From<&JsonValue> for Launcher {
                 None => default.repairer
             },
 
+            logs: value.get("logs")
+                .map(GameLogs::from)
+                .unwrap_or(default.logs),
+
+            playtime_limits: value.get("playtime_limits")
+                .map(PlaytimeLimits::from)
+                .unwrap_or(default.playtime_limits),
+
             #[cfg(feature = "environment-emulation")]
             environment: match value.get("environment") {
                 Some(value) => serde_json::from_value(value.clone()).unwrap_or(default.environment),
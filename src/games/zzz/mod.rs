@@ -12,5 +12,8 @@ pub mod env_emulation;
 #[cfg(feature = "game")]
 pub mod game;
 
+#[cfg(feature = "game")]
+pub mod diagnostics;
+
 #[cfg(feature = "sessions")]
 pub mod sessions;
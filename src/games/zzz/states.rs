@@ -6,6 +6,15 @@ use anime_game_core::zzz::prelude::*;
 use crate::config::ConfigExt;
 use crate::zzz::config::Config;
 
+#[cfg(feature = "components")]
+use crate::components::runner::RunnerKind;
+
+#[cfg(feature = "sandbox")]
+use crate::config::schema_blanks::sandbox::SandboxBackend;
+
+#[cfg(feature = "game")]
+use crate::launch::playtime::{PlaytimeAllowance, SystemClock};
+
 #[derive(Debug, Clone)]
 pub enum LauncherState {
     Launch,
@@ -26,6 +35,21 @@ pub enum LauncherState {
     #[cfg(feature = "components")]
     WineNotInstalled,
 
+    /// Selected Proton build is started through the `umu-run`
+    /// which is not installed
+    #[cfg(feature = "components")]
+    UmuNotInstalled,
+
+    /// Selected sandbox backend is not installed
+    /// or can't be used on the current system
+    #[cfg(feature = "sandbox")]
+    SandboxNotAvailable(SandboxBackend),
+
+    /// Game can't be played now because of the playtime limits.
+    /// Never contains allowed playtime
+    #[cfg(feature = "game")]
+    PlaytimeNotAllowed(PlaytimeAllowance),
+
     PrefixNotExists,
 
     DxvkNotInstalled,
@@ -185,14 +209,47 @@ impl LauncherState {
             _ => ()
         }
 
-        Self::get(LauncherStateParams {
+        // Proton builds can't be started without the umu-run
+        #[cfg(feature = "components")]
+        if let Some(selected) = &config.game.wine.selected {
+            let kind = RunnerKind::detect(config.game.wine.builds.join(selected));
+
+            if kind == Some(RunnerKind::Proton) && crate::find_binary(&config.game.wine.umu.binary).is_none() {
+                return Ok(Self::UmuNotInstalled);
+            }
+        }
+
+        #[cfg(feature = "sandbox")]
+        if config.sandbox.is_enabled() && !config.sandbox.backend.is_available() {
+            return Ok(Self::SandboxNotAvailable(config.sandbox.backend));
+        }
+
+        // Telemetry servers blocked in the sandbox don't need to be checked
+        #[cfg(feature = "sandbox")]
+        let check_telemetry = config.launcher.disable_telemetry && !config.sandbox.blocks_telemetry();
+
+        #[cfg(not(feature = "sandbox"))]
+        let check_telemetry = config.launcher.disable_telemetry;
+
+        #[cfg(feature = "game")]
+        let allowance = crate::zzz::game::get_playtime_allowance(&config, &SystemClock)?;
+
+        let state = Self::get(LauncherStateParams {
             game_path: config.game.path.for_edition(config.launcher.edition).to_path_buf(),
             game_edition: config.launcher.edition,
             wine_prefix: config.game.wine.prefix,
 
-            disable_telemetry: config.launcher.disable_telemetry,
+            disable_telemetry: check_telemetry,
 
             status_updater
-        })
+        })?;
+
+        // Playtime limits don't prevent the game from being updated
+        #[cfg(feature = "game")]
+        if matches!(state, Self::Launch | Self::PredownloadAvailable { .. }) && !allowance.is_allowed() {
+            return Ok(Self::PlaytimeNotAllowed(allowance));
+        }
+
+        Ok(state)
     }
 }
@@ -0,0 +1,394 @@
+//! Classification of the game log output
+//!
+//! Game logs contain output of wine, DXVK, VKD3D and the game itself.
+//! Analyzer groups interesting lines into typed findings and matches them
+//! against the tables of known failures, so frontends can explain
+//! to the user why the game didn't start.
+
+use std::collections::HashMap;
+
+use super::logs::SessionLog;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Known failure which can be detected from the game log
+pub struct Signature {
+    /// Unique identifier of the failure
+    pub id: &'static str,
+
+    /// Short description of the failure
+    pub title: &'static str,
+
+    /// Advice for the user on how to fix the failure
+    pub hint: &'static str,
+
+    /// Substrings which all must be present in a single log line
+    pub patterns: &'static [&'static str]
+}
+
+impl Signature {
+    #[inline]
+    /// Check if the log line matches the signature
+    pub fn matches(&self, line: &str) -> bool {
+        !self.patterns.is_empty() && self.patterns.iter().all(|pattern| line.contains(pattern))
+    }
+}
+
+/// Failures which are not specific to any game
+///
+/// Log excerpts matched by every signature are stored in `tests/fixtures/logs`
+///
+/// ```
+/// use anime_launcher_sdk::launch::analyzer::{analyze, Finding};
+///
+/// let log = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/logs/broken-prefix.log"));
+///
+/// assert!(matches!(&analyze(log, &[])[0], Finding::KnownFailure { signature, .. } if signature.id == "broken-prefix"));
+/// ```
+///
+/// ```
+/// use anime_launcher_sdk::launch::analyzer::{analyze, Finding};
+///
+/// let log = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/logs/vulkan-unavailable.log"));
+///
+/// assert!(matches!(&analyze(log, &[])[0], Finding::KnownFailure { signature, .. } if signature.id == "vulkan-unavailable"));
+/// ```
+pub const COMMON_SIGNATURES: &[Signature] = &[
+    Signature {
+        id: "broken-prefix",
+        title: "Wine prefix is broken",
+        hint: "Delete the wine prefix and let the launcher create it again",
+        patterns: &["could not load kernel32.dll"]
+    },
+    Signature {
+        id: "vulkan-unavailable",
+        title: "Vulkan is not available",
+        hint: "Install vulkan drivers for your GPU, including the 32 bit ones",
+        patterns: &["Failed to create Vulkan"]
+    }
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WineLevel {
+    Err,
+    Fixme
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Problem found in the game log
+pub enum Finding {
+    /// Line matched a known failure signature
+    KnownFailure {
+        signature: Signature,
+        line: String
+    },
+
+    /// DLL required by the game is not installed to the wine prefix
+    MissingDll {
+        name: String,
+
+        /// Library or executable which has requested the DLL
+        needed_by: Option<String>
+    },
+
+    /// Process has crashed with an unhandled page fault
+    PageFault {
+        message: String
+    },
+
+    /// Stack trace printed by winedbg after the crash
+    Backtrace {
+        frames: Vec<String>
+    },
+
+    /// DXVK error, repeated `count` times
+    DxvkError {
+        message: String,
+        count: usize
+    },
+
+    /// VKD3D error, repeated `count` times
+    Vkd3dError {
+        message: String,
+        count: usize
+    },
+
+    /// Wine `err:` or `fixme:` messages of a single debug channel
+    WineMessages {
+        level: WineLevel,
+        channel: String,
+        count: usize,
+
+        /// First message of the channel
+        example: String
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Line<'a> {
+    Wine {
+        level: WineLevel,
+        channel: &'a str,
+        message: &'a str
+    },
+
+    Vkd3d {
+        level: WineLevel,
+        message: &'a str
+    },
+
+    Dxvk {
+        message: &'a str
+    },
+
+    Other
+}
+
+/// Remove `[time] [stream]` prefixes added by the launcher
+fn strip_prefix(line: &str) -> &str {
+    let mut line = line.trim_end_matches('\r');
+
+    for _ in 0..2 {
+        if let Some(rest) = line.strip_prefix('[').and_then(|rest| rest.split_once("] ")) {
+            line = rest.1;
+        }
+    }
+
+    line
+}
+
+fn parse_level(line: &str) -> Option<(WineLevel, &str)> {
+    if let Some(rest) = line.strip_prefix("err:") {
+        Some((WineLevel::Err, rest))
+    }
+
+    else {
+        line.strip_prefix("fixme:").map(|rest| (WineLevel::Fixme, rest))
+    }
+}
+
+fn classify(line: &str) -> Line<'_> {
+    // DXVK aligns messages with spaces: "err:   D3D11Device: ..."
+    if let Some(message) = line.strip_prefix("err:") {
+        if message.starts_with(' ') {
+            return Line::Dxvk {
+                message: message.trim_start()
+            };
+        }
+    }
+
+    // Wine and VKD3D prefix messages with thread ids: "0024:0028:err:..."
+    let mut rest = line;
+
+    loop {
+        if let Some((level, message)) = parse_level(rest) {
+            let Some((channel, message)) = message.split_once(':')
+            else {
+                return Line::Other;
+            };
+
+            // Wine: "err:channel:function message"
+            // VKD3D: "err:function: message"
+            if message.starts_with(' ') {
+                return Line::Vkd3d {
+                    level,
+                    message: message.trim_start()
+                };
+            }
+
+            return Line::Wine {
+                level,
+                channel,
+                message
+            };
+        }
+
+        match rest.split_once(':') {
+            Some((id, tail)) if !id.is_empty() && id.chars().all(|c| c.is_ascii_hexdigit()) => rest = tail,
+
+            _ => return Line::Other
+        }
+    }
+}
+
+/// Parse `Library MFC140.dll (which is needed by L"...") not found`
+fn parse_missing_dll(message: &str) -> Option<(String, Option<String>)> {
+    let (_, library) = message.split_once("Library ")?;
+
+    if !library.contains("not found") {
+        return None;
+    }
+
+    let name = library.split_whitespace().next()?.to_string();
+
+    // Wine escapes backslashes in the debug strings
+    let needed_by = library.split_once("needed by L\"")
+        .and_then(|(_, path)| path.split_once('"'))
+        .map(|(path, _)| path.replace("\\\\", "\\"));
+
+    Some((name, needed_by))
+}
+
+/// Analyze game log
+///
+/// Lines are matched against `COMMON_SIGNATURES` and given ones.
+/// Findings are sorted by their importance: known failures go first,
+/// wine messages go last
+///
+/// ```
+/// use anime_launcher_sdk::launch::analyzer::{analyze, Finding, WineLevel};
+///
+/// let log = r#"[2026-10-17 12:00:00.000] [stderr] 0024:fixme:ntdll:NtQuerySystemInformation info_class SYSTEM_PERFORMANCE_INFORMATION
+/// [2026-10-17 12:00:00.001] [stderr] 0024:fixme:ntdll:NtQuerySystemInformation info_class SYSTEM_PERFORMANCE_INFORMATION
+/// [2026-10-17 12:00:00.002] [stderr] 0024:err:module:import_dll Library MFC140.dll (which is needed by L"C:\\Game\\StarRail.exe") not found
+/// [2026-10-17 12:00:00.003] [stderr] err:   D3D11Device: CreateTexture2D: Unsupported format
+/// [2026-10-17 12:00:00.004] [stderr] 0130:err:d3d12_device_create_root_signature: Failed to create root signature
+/// [2026-10-17 12:00:00.005] [stderr] wine: Unhandled page fault on read access to 0000000000000000 at address 00000001401D2A3B (thread 0124), starting debugger...
+/// [2026-10-17 12:00:00.006] [stdout] Backtrace:
+/// [2026-10-17 12:00:00.007] [stdout] =>0 0x00000001401d2a3b in starrail (+0x1d2a3b)
+/// [2026-10-17 12:00:00.008] [stdout]   1 0x000000014001a2b0 in starrail (+0x1a2b0)
+/// [2026-10-17 12:00:00.009] [stdout] Modules:"#;
+///
+/// let findings = analyze(log, &[]);
+///
+/// assert_eq!(findings[0], Finding::MissingDll {
+///     name: String::from("MFC140.dll"),
+///     needed_by: Some(String::from("C:\\Game\\StarRail.exe"))
+/// });
+///
+/// assert!(matches!(&findings[1], Finding::PageFault { message } if message.contains("0000000000000000")));
+///
+/// assert_eq!(findings[2], Finding::Backtrace {
+///     frames: vec![
+///         String::from("=>0 0x00000001401d2a3b in starrail (+0x1d2a3b)"),
+///         String::from("1 0x000000014001a2b0 in starrail (+0x1a2b0)")
+///     ]
+/// });
+///
+/// assert_eq!(findings[3], Finding::DxvkError {
+///     message: String::from("D3D11Device: CreateTexture2D: Unsupported format"),
+///     count: 1
+/// });
+///
+/// assert_eq!(findings[4], Finding::Vkd3dError {
+///     message: String::from("Failed to create root signature"),
+///     count: 1
+/// });
+///
+/// assert!(matches!(&findings[5], Finding::WineMessages { level: WineLevel::Err, channel, count: 1, .. } if channel == "module"));
+/// assert!(matches!(&findings[6], Finding::WineMessages { level: WineLevel::Fixme, channel, count: 2, .. } if channel == "ntdll"));
+///
+/// assert_eq!(findings.len(), 7);
+/// ```
+pub fn analyze(log: &str, signatures: &[Signature]) -> Vec<Finding> {
+    let mut known = Vec::new();
+    let mut missing_dlls = Vec::<Finding>::new();
+    let mut page_faults = Vec::new();
+    let mut backtraces = Vec::new();
+
+    let mut dxvk = Vec::<(String, usize)>::new();
+    let mut vkd3d = Vec::<(String, usize)>::new();
+
+    let mut wine = Vec::<(WineLevel, String, usize, String)>::new();
+    let mut wine_index = HashMap::<(WineLevel, String), usize>::new();
+
+    let mut backtrace: Option<Vec<String>> = None;
+
+    for line in log.lines() {
+        let line = strip_prefix(line);
+
+        // Collect backtrace frames until the first non-frame line
+        if let Some(frames) = &mut backtrace {
+            let frame = line.trim_start();
+
+            if frame.starts_with("=>") || frame.starts_with(|c: char| c.is_ascii_digit()) {
+                frames.push(frame.to_string());
+
+                continue;
+            }
+
+            if let Some(frames) = backtrace.take() {
+                backtraces.push(Finding::Backtrace { frames });
+            }
+        }
+
+        if line.trim() == "Backtrace:" {
+            backtrace = Some(Vec::new());
+
+            continue;
+        }
+
+        for signature in COMMON_SIGNATURES.iter().chain(signatures) {
+            let is_new = !known.iter().any(|finding| matches!(finding, Finding::KnownFailure { signature: found, .. } if found.id == signature.id));
+
+            if is_new && signature.matches(line) {
+                known.push(Finding::KnownFailure {
+                    signature: *signature,
+                    line: line.to_string()
+                });
+            }
+        }
+
+        if line.contains("Unhandled page fault") || line.contains("Unhandled exception: page fault") {
+            page_faults.push(Finding::PageFault {
+                message: line.to_string()
+            });
+
+            continue;
+        }
+
+        match classify(line) {
+            Line::Dxvk { message } => match dxvk.iter_mut().find(|(known, _)| known == message) {
+                Some((_, count)) => *count += 1,
+                None => dxvk.push((message.to_string(), 1))
+            }
+
+            Line::Vkd3d { level: WineLevel::Err, message } => match vkd3d.iter_mut().find(|(known, _)| known == message) {
+                Some((_, count)) => *count += 1,
+                None => vkd3d.push((message.to_string(), 1))
+            }
+
+            Line::Wine { level, channel, message } => {
+                if let Some((name, needed_by)) = parse_missing_dll(message) {
+                    let is_new = !missing_dlls.iter().any(|finding| matches!(finding, Finding::MissingDll { name: found, .. } if found.eq_ignore_ascii_case(&name)));
+
+                    if is_new {
+                        missing_dlls.push(Finding::MissingDll { name, needed_by });
+                    }
+                }
+
+                match wine_index.get(&(level, channel.to_string())) {
+                    Some(i) => wine[*i].2 += 1,
+
+                    None => {
+                        wine_index.insert((level, channel.to_string()), wine.len());
+                        wine.push((level, channel.to_string(), 1, message.to_string()));
+                    }
+                }
+            }
+
+            Line::Vkd3d { .. } | Line::Other => ()
+        }
+    }
+
+    if let Some(frames) = backtrace {
+        backtraces.push(Finding::Backtrace { frames });
+    }
+
+    // Errors before fixmes
+    wine.sort_by_key(|(level, ..)| *level == WineLevel::Fixme);
+
+    known.into_iter()
+        .chain(missing_dlls)
+        .chain(page_faults)
+        .chain(backtraces)
+        .chain(dxvk.into_iter().map(|(message, count)| Finding::DxvkError { message, count }))
+        .chain(vkd3d.into_iter().map(|(message, count)| Finding::Vkd3dError { message, count }))
+        .chain(wine.into_iter().map(|(level, channel, count, example)| Finding::WineMessages { level, channel, count, example }))
+        .collect()
+}
+
+#[inline]
+/// Analyze log of the game session
+pub fn analyze_session(log: &SessionLog, signatures: &[Signature]) -> anyhow::Result<Vec<Finding>> {
+    Ok(analyze(&log.read()?, signatures))
+}
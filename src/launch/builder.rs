@@ -0,0 +1,480 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::components::runner::Runner;
+use crate::components::dxvk::Version as DxvkVersion;
+use crate::config::schema_blanks::prelude::*;
+
+#[cfg(feature = "sandbox")]
+use crate::config::schema_blanks::sandbox::{hosts, SandboxPath};
+
+use crate::shell;
+
+use super::keywords::Keywords;
+use super::services::Service;
+use super::{LaunchPlan, Wineserver, Wrapper};
+
+/// Launch settings which all the games store the same way
+///
+/// Use `launch_config!` to fill it from the game config
+pub struct LaunchConfig<'a> {
+    /// Game folder of the selected edition
+    pub game: PathBuf,
+
+    pub temp: PathBuf,
+    pub disable_telemetry: bool,
+    pub logs: GameLogs,
+    pub playtime_limits: &'a PlaytimeLimits,
+    pub components: &'a Path,
+
+    /// Selected wine or proton build
+    pub runner: Option<Runner>,
+
+    /// Selected DXVK version
+    pub dxvk: Option<DxvkVersion>,
+
+    pub prefix: &'a Path,
+    pub builds: &'a Path,
+    pub sync: &'a WineSync,
+    pub language: &'a WineLang,
+    pub borderless: bool,
+    pub drives: &'a WineDrives,
+    pub virtual_desktop: &'a VirtualDesktop,
+    pub shared_libraries: &'a SharedLibraries,
+    pub timeout_fix: bool,
+    pub umu: &'a Umu,
+    pub steam_runtime: &'a SteamRuntime,
+
+    pub fsr: &'a Fsr,
+    pub gamemode: bool,
+    pub hud: &'a HUD,
+    pub gamescope: &'a Gamescope,
+    pub wrappers: &'a [CustomWrapper],
+    pub cpu: &'a CpuScheduling,
+    pub inhibit: &'a SleepInhibit,
+
+    pub environment: &'a HashMap<String, String>,
+    pub command: Option<&'a str>,
+    pub hooks: &'a GameHooks,
+    pub limits: &'a ResourceLimits,
+
+    #[cfg(feature = "sandbox")]
+    pub sandbox: &'a Sandbox
+}
+
+/// Fill `LaunchConfig` from the game config schema
+///
+/// Selected runner is read with `?`, so the macro can only
+/// be used in functions returning `anyhow::Result`
+#[macro_export]
+macro_rules! launch_config {
+    ($config:ident) => {
+        $crate::launch::LaunchConfig {
+            game: $config.game.path.for_edition($config.launcher.edition).to_path_buf(),
+            temp: $config.launcher.temp.clone().unwrap_or(std::env::temp_dir()),
+            disable_telemetry: $config.launcher.disable_telemetry,
+            logs: $config.launcher.logs,
+            playtime_limits: &$config.launcher.playtime_limits,
+            components: &$config.components.path,
+            runner: $config.get_selected_runner()?,
+            dxvk: $config.get_selected_dxvk().ok().flatten(),
+
+            prefix: &$config.game.wine.prefix,
+            builds: &$config.game.wine.builds,
+            sync: &$config.game.wine.sync,
+            language: &$config.game.wine.language,
+            borderless: $config.game.wine.borderless,
+            drives: &$config.game.wine.drives,
+            virtual_desktop: &$config.game.wine.virtual_desktop,
+            shared_libraries: &$config.game.wine.shared_libraries,
+            timeout_fix: $config.game.wine.timeout_fix,
+            umu: &$config.game.wine.umu,
+            steam_runtime: &$config.game.wine.steam_runtime,
+
+            fsr: &$config.game.enhancements.fsr,
+            gamemode: $config.game.enhancements.gamemode,
+            hud: &$config.game.enhancements.hud,
+            gamescope: &$config.game.enhancements.gamescope,
+            wrappers: &$config.game.enhancements.wrappers,
+            cpu: &$config.game.enhancements.cpu,
+            inhibit: &$config.game.enhancements.inhibit,
+
+            environment: &$config.game.environment,
+            command: $config.game.command.as_deref(),
+            hooks: &$config.game.hooks,
+            limits: &$config.game.limits,
+
+            #[cfg(feature = "sandbox")]
+            sandbox: &$config.sandbox
+        }
+    };
+}
+
+/// Launch inputs which differ between the games
+#[derive(Debug, Clone, Default)]
+pub struct GameSpecific {
+    /// Name of the game executable. Used as `%exe%` keyword
+    pub executable: String,
+
+    /// Windows command which starts the game, e.g. the executable
+    /// or the patch loader followed by the executable
+    pub windows_command: Vec<String>,
+
+    /// Game patch folder. Used as `%patch%` keyword and shared
+    /// with the sandbox and the Steam Linux Runtime container
+    pub patch: Option<PathBuf>,
+
+    /// Name of the wine virtual desktop
+    pub virtual_desktop: String,
+
+    /// Start the game in DirectX 12 mode
+    pub dx12: bool,
+
+    /// Set `WINE_ENABLE_TIMEOUT_FIX`
+    pub timeout_fix: bool,
+
+    /// Unset `DISPLAY` so wine uses the wayland driver
+    pub winewayland: bool,
+
+    /// Windows commands started by the same runner in background, e.g. FPS unlocker
+    pub background: Vec<Vec<String>>,
+
+    /// Additional sandbox mounts: host folder and its sandbox path
+    pub mounts: Vec<(PathBuf, String)>,
+
+    /// Game edition and version, used as keywords
+    pub edition: String,
+    pub game_version: String,
+
+    /// Servers blocked in the sandbox hosts file
+    pub telemetry_servers: Vec<String>,
+
+    /// Default `GAMEID` of the umu-run
+    pub umu_game_id: String,
+
+    /// Game folder name in the launcher folders, e.g. for limits scope name
+    pub folder_name: String,
+
+    pub launcher_dir: PathBuf
+}
+
+#[derive(Debug, Clone)]
+struct Folders {
+    pub wine: PathBuf,
+    pub prefix: PathBuf,
+    pub game: PathBuf,
+    pub patch: Option<PathBuf>,
+    pub temp: PathBuf
+}
+
+fn get_keywords(folders: &Folders, wine_name: &str, dxvk: Option<&DxvkVersion>, game: &GameSpecific) -> Keywords {
+    let mut keywords = Keywords::new()
+        .with("build", folders.wine.to_string_lossy())
+        .with("prefix", folders.prefix.to_string_lossy())
+        .with("game", folders.game.to_string_lossy())
+        .with("temp", folders.temp.to_string_lossy())
+        .with("launcher", game.launcher_dir.to_string_lossy());
+
+    if let Some(patch) = &folders.patch {
+        keywords = keywords.with("patch", patch.to_string_lossy());
+    }
+
+    keywords
+        .with("wine_name", wine_name)
+        .with("dxvk_version", dxvk.map(|dxvk| dxvk.version.as_str()).unwrap_or_default())
+        .with("edition", &game.edition)
+        .with("game_version", &game.game_version)
+        .with("exe", &game.executable)
+}
+
+impl LaunchPlan {
+    /// Build the game launch plan from the shared launch settings
+    /// and the game specific inputs
+    ///
+    /// This function has no side effects: it doesn't touch the wine prefix
+    /// nor the game folder
+    pub fn from_config(config: &LaunchConfig, game: GameSpecific) -> anyhow::Result<Self> {
+        if !config.game.exists() {
+            return Err(anyhow::anyhow!("Game is not installed"));
+        }
+
+        let Some(runner) = &config.runner
+        else {
+            anyhow::bail!("Couldn't find wine executable");
+        };
+
+        let features = runner.features(config.components)?;
+
+        let folders = Folders {
+            wine: config.builds.join(runner.name()),
+            prefix: config.prefix.to_path_buf(),
+            game: config.game.clone(),
+            patch: game.patch.clone(),
+            temp: config.temp.clone()
+        };
+
+        // Prepare launch plan
+        // %command% = %bash_command% %windows_command% %launch_args%
+
+        // We use real current dir here because sandboxed one
+        // obviously doesn't exist
+        let mut plan = LaunchPlan::new(&config.game);
+
+        plan.keywords = get_keywords(&folders, runner.name(), config.dxvk.as_ref(), &game);
+
+        plan.wineserver = Some(Wineserver {
+            binary: runner.get_wineserver(&folders.wine),
+            prefix: folders.prefix.clone()
+        });
+
+        // systemd-run --user --scope -p <limits> <sandbox> -- <command to run>
+        //
+        // Limits apply to the sandbox as well, so they're not a part of the wrappers chain
+        if let Some(limits) = config.limits.get_wrapper(&game.folder_name) {
+            plan.limits = Some(Wrapper::new(limits.command));
+            plan.files.extend(limits.files);
+        }
+
+        // <outer wrappers> gamescope <params> -- taskset nice ionice gamemoderun <inner wrappers> <command to run>
+        plan.add_wrappers(config.wrappers, WrapperPosition::Outer)?;
+
+        // gamescope <params> -- <command to run>
+        if let Some(gamescope) = config.gamescope.get_args()? {
+            plan.wrappers.push(Wrapper::new(gamescope).with_separator());
+        }
+
+        // Cores list is validated against the online cores
+        let cpu_cores = config.cpu.get_cores()?;
+
+        for wrapper in config.cpu.get_wrappers(cpu_cores.as_deref()) {
+            plan.wrappers.push(Wrapper::new(wrapper));
+        }
+
+        if config.gamemode {
+            plan.wrappers.push(Wrapper::new(["gamemoderun"]));
+        }
+
+        plan.add_wrappers(config.wrappers, WrapperPosition::Inner)?;
+
+        // Steam Linux Runtime container wraps only the wine itself
+        // <steam runtime> -- <command to run>
+        if let Some(runtime) = config.steam_runtime.get_command()? {
+            if let Runner::Proton(_) = runner {
+                anyhow::bail!("Proton builds are already started inside of the Steam Linux Runtime by the umu-run");
+            }
+
+            // pressure-vessel uses bwrap itself and can't be nested into our sandbox
+            #[cfg(feature = "sandbox")]
+            if config.sandbox.is_enabled() {
+                anyhow::bail!("Steam Linux Runtime can't be used together with the sandbox");
+            }
+
+            // Background commands would be started outside of the container
+            // and couldn't reach the wineserver of the game
+            if !game.background.is_empty() {
+                anyhow::bail!("Steam Linux Runtime can't be used together with background commands like FPS unlocker");
+            }
+
+            plan.wrappers.push(Wrapper::new(runtime).with_separator());
+        }
+
+        let run_command: Vec<String> = match features.command {
+            Some(command) => shell::split(command)?
+                .into_iter()
+                .map(|arg| plan.keywords.replace(arg))
+                .collect::<anyhow::Result<Vec<_>>>()?,
+
+            None => match runner {
+                Runner::Wine(wine) => vec![folders.wine.join(wine.files.wine64.as_ref().unwrap_or(&wine.files.wine))
+                    .to_string_lossy()
+                    .to_string()],
+
+                // umu-run <windows command>
+                Runner::Proton(_) => vec![config.umu.binary.clone()]
+            }
+        };
+
+        plan.runner.extend(run_command.clone());
+
+        if let Some(virtual_desktop) = config.virtual_desktop.get_args(&game.virtual_desktop) {
+            plan.windows_command.extend(virtual_desktop);
+        }
+
+        plan.windows_command.extend(game.windows_command);
+
+        if game.dx12 {
+            plan.launch_args.push(String::from("-use-d3d12"));
+        }
+
+        if config.borderless {
+            plan.launch_args.extend([
+                String::from("-screen-fullscreen"),
+                String::from("0"),
+                String::from("-popupwindow")
+            ]);
+        }
+
+        // https://notabug.org/Krock/dawn/src/master/TWEAKS.md
+        if config.fsr.enabled {
+            plan.launch_args.extend([
+                String::from("-window-mode"),
+                String::from("exclusive")
+            ]);
+        }
+
+        // Background commands like FPS unlocker are started together with the game
+        for command in game.background {
+            let mut background = run_command.clone();
+
+            background.extend(command);
+
+            plan.background.push(background);
+        }
+
+        // Keep the screen and the system awake until all the game processes have exited
+        if let Some(inhibitor) = config.inhibit.get_command(&game.folder_name) {
+            if crate::is_available("systemd-inhibit") {
+                plan.services.push(Service::new("systemd-inhibit", inhibitor));
+            }
+
+            else {
+                tracing::warn!("systemd-inhibit is not available. Screen locking and suspend are not inhibited");
+            }
+        }
+
+        // Use user-given launch command
+        plan.template = config.command.map(String::from);
+
+        // Hooks use host paths since they run outside of the sandbox
+        plan.add_hooks(config.hooks)?;
+
+        // bwrap/firejail <params> -- <command to run>
+        //
+        // Plan keeps host paths, they're translated to the sandbox ones
+        // when the final command is built
+        #[cfg(feature = "sandbox")]
+        if config.sandbox.is_enabled() {
+            // Report mistakes in the mounts before the prefix is modified
+            config.sandbox.validate()?;
+
+            let mut paths = config.sandbox.get_paths(
+                folders.wine.to_string_lossy(),
+                folders.prefix.to_string_lossy(),
+                folders.game.to_string_lossy()
+            )?;
+
+            for (host, sandbox) in &game.mounts {
+                paths.insert(config.sandbox.map_path(SandboxPath::bind(host.to_string_lossy(), sandbox)))?;
+            }
+
+            if let Some(patch) = &folders.patch {
+                paths.insert(config.sandbox.map_path(SandboxPath::bind(patch.to_string_lossy(), "/tmp/sandbox/patch")))?;
+            }
+
+            // umu-run keeps steam runtime in its data folder
+            if let Runner::Proton(_) = runner {
+                if let Some(umu) = Umu::get_data_folder().filter(|folder| folder.exists()) {
+                    paths.insert(SandboxPath::bind(umu.to_string_lossy(), umu.to_string_lossy()))?;
+                }
+            }
+
+            // Replace the session bus with the filtering proxy
+            if let Some(proxy) = config.sandbox.get_dbus_proxy()? {
+                paths.insert(proxy.get_path())?;
+
+                plan.env("sandbox dbus", "DBUS_SESSION_BUS_ADDRESS", proxy.get_address());
+                plan.services.push(Service::new("xdg-dbus-proxy", proxy.command).with_ready_path(proxy.socket));
+            }
+
+            // Block telemetry servers for the game only, without editing the system hosts file
+            if config.disable_telemetry && config.sandbox.blocks_telemetry() {
+                let hosts_file = game.launcher_dir.join("sandbox-hosts");
+
+                paths.insert(SandboxPath::read_only(hosts_file.to_string_lossy(), hosts::HOSTS_FILE))?;
+
+                plan.files.insert(hosts_file, hosts::generate_hosts_file(
+                    hosts::read_system_hosts(),
+                    &game.telemetry_servers
+                ));
+            }
+
+            paths.insert(config.sandbox.map_path(SandboxPath::translate(folders.temp.to_string_lossy(), "/tmp")))?;
+
+            let mounts = config.sandbox.get_mounts(&paths);
+
+            let mut sandbox = config.sandbox.get_args_with_mounts(&mounts)?;
+
+            sandbox.extend(config.sandbox.get_chdir_args(paths.translate(folders.game.to_string_lossy())));
+
+            plan.sandbox = Some(Wrapper::new(sandbox).with_separator());
+
+            plan.mounts = mounts;
+            plan.paths = paths;
+        }
+
+        // Setup environment
+
+        plan.env("wine", "WINEARCH", "win64");
+        plan.env("wine", "WINEPREFIX", folders.prefix.to_string_lossy());
+
+        // Proton builds are started by the umu-run which needs these variables
+        if let Runner::Proton(proton) = runner {
+            let game_id = config.umu.game_id.as_deref().unwrap_or(game.umu_game_id.as_str());
+
+            plan.envs("proton", proton.get_env_vars(&folders.prefix, &folders.game, game_id));
+        }
+
+        // Add environment flags for selected wine
+        for (key, value) in features.env.into_iter() {
+            plan.env("wine features", key, plan.keywords.replace_env(value));
+        }
+
+        // Add environment flags for selected dxvk
+        if let Some(dxvk) = &config.dxvk {
+            if let Ok(Some(features)) = dxvk.features(config.components) {
+                for (key, value) in features.env.iter() {
+                    plan.env("dxvk features", key, plan.keywords.replace_env(value));
+                }
+            }
+        }
+
+        let wine_folder = folders.wine.clone();
+
+        plan.envs("hud", config.hud.get_env_vars(config.gamescope.enabled));
+        plan.envs("fsr", config.fsr.get_env_vars());
+
+        plan.envs("wine sync", config.sync.get_env_vars());
+        plan.envs("cpu cores", config.cpu.get_env_vars(cpu_cores.as_deref()));
+        plan.envs("wine language", config.language.get_env_vars());
+        plan.append_envs("shared libraries", config.shared_libraries.get_env_vars(wine_folder));
+
+        // Expose game files to the Steam Linux Runtime container
+        let mut shared_folders = vec![&folders.game, &folders.prefix, &folders.wine];
+
+        shared_folders.extend(&folders.patch);
+
+        plan.append_envs("steam runtime", config.steam_runtime.get_env_vars(shared_folders));
+
+        // enable dxvk-nvapi when launching in DX12 mode
+        // https://github.com/jp7677/dxvk-nvapi/blob/bfd44821a77fc591635ae0e56c0b0e49cb26d3a5/README.md#wine--wine-staging
+        if game.dx12 {
+            plan.env("dx12", "DXVK_ENABLE_NVAPI", "1");
+        }
+
+        if game.timeout_fix {
+            plan.env("timeout fix", "WINE_ENABLE_TIMEOUT_FIX", "1");
+        }
+
+        if game.winewayland {
+            plan.env("winewayland", "DISPLAY", "");
+        }
+
+        plan.add_wrappers_env(config.wrappers);
+
+        for (key, value) in config.environment {
+            plan.env("game environment", key, plan.keywords.replace_env(value));
+        }
+
+        Ok(plan)
+    }
+}
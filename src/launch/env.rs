@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EnvMode {
+    /// Replace value set by the previous layers
+    Set,
+
+    /// Append value to the one set by the previous layers,
+    /// separated by the variable separator
+    Append
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Value of the environment variable set by some layer
+pub struct EnvValue {
+    pub value: String,
+
+    /// Name of the setting which has set this value
+    pub source: String,
+
+    pub mode: EnvMode
+}
+
+/// Get separator of the path-like variable values
+///
+/// ```
+/// use anime_launcher_sdk::launch::env_separator;
+///
+/// assert_eq!(env_separator("LD_LIBRARY_PATH"), ":");
+/// assert_eq!(env_separator("WINEDLLOVERRIDES"), ";");
+/// ```
+pub fn env_separator(name: &str) -> &'static str {
+    match name {
+        "WINEDLLOVERRIDES" => ";",
+
+        _ => ":"
+    }
+}
+
+/// Get value of the host variable extended by the layers
+///
+/// Appended values extend the host value unless
+/// some of the layers has set its own one
+fn inherited_value(name: &str, layers: &[EnvValue]) -> Option<String> {
+    if layers.iter().any(|layer| layer.mode == EnvMode::Set) {
+        return None;
+    }
+
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// Compute final value of the variable from its layers
+fn resolve_value(name: &str, layers: &[EnvValue]) -> String {
+    let mut value = inherited_value(name, layers).unwrap_or_default();
+
+    for layer in layers {
+        match layer.mode {
+            EnvMode::Set => value = layer.value.clone(),
+
+            EnvMode::Append if value.is_empty() => value = layer.value.clone(),
+
+            EnvMode::Append => {
+                if !layer.value.is_empty() {
+                    value += env_separator(name);
+                    value += &layer.value;
+                }
+            }
+        }
+    }
+
+    value
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Environment of the game built from the layers of settings
+///
+/// Every variable remembers which layers have set it, so it's possible
+/// to explain why it has its final value. Later layers override
+/// values of the earlier ones unless they were appended. Variables
+/// which were only appended extend their value from the host system
+///
+/// ```
+/// use anime_launcher_sdk::launch::{EnvLayers, EnvMode};
+///
+/// let mut env = EnvLayers::default();
+///
+/// env.set("wine features", "WINEDLLOVERRIDES", "winemenubuilder.exe=d");
+/// env.append("dxvk features", "WINEDLLOVERRIDES", "d3d11=n");
+///
+/// env.set("wine features", "LD_LIBRARY_PATH", "/old");
+/// env.set("shared libraries", "LD_LIBRARY_PATH", "/wine/lib");
+///
+/// // Variable names can't contain `+`, so it's used to append values
+/// env.add("game environment", "LD_LIBRARY_PATH+", "/my/lib");
+///
+/// assert_eq!(env.get("WINEDLLOVERRIDES").unwrap(), "winemenubuilder.exe=d;d3d11=n");
+/// assert_eq!(env.get("LD_LIBRARY_PATH").unwrap(), "/wine/lib:/my/lib");
+///
+/// let report = env.report();
+///
+/// assert_eq!(report[0].name, "LD_LIBRARY_PATH");
+/// assert_eq!(report[0].source, "game environment");
+/// assert_eq!(report[0].overridden()[0].source, "wine features");
+///
+/// assert_eq!(report[0].explain(), [
+///     "LD_LIBRARY_PATH=/wine/lib:/my/lib",
+///     "  overridden by shared libraries: /old (wine features)",
+///     "  set by shared libraries: /wine/lib",
+///     "  appended by game environment: /my/lib"
+/// ].join("\n"));
+///
+/// std::env::set_var("ANIME_LAUNCHER_SDK_ENV_TEST", "/host/lib");
+///
+/// env.append("shared libraries", "ANIME_LAUNCHER_SDK_ENV_TEST", "/wine/lib");
+///
+/// assert_eq!(env.get("ANIME_LAUNCHER_SDK_ENV_TEST").unwrap(), "/host/lib:/wine/lib");
+/// ```
+pub struct EnvLayers {
+    vars: HashMap<String, Vec<EnvValue>>
+}
+
+impl EnvLayers {
+    fn push(&mut self, source: impl ToString, name: impl ToString, value: impl ToString, mode: EnvMode) {
+        self.vars.entry(name.to_string())
+            .or_default()
+            .push(EnvValue {
+                value: value.to_string(),
+                source: source.to_string(),
+                mode
+            });
+    }
+
+    #[inline]
+    /// Set variable, overriding its previous value
+    pub fn set(&mut self, source: impl ToString, name: impl ToString, value: impl ToString) {
+        self.push(source, name, value, EnvMode::Set);
+    }
+
+    #[inline]
+    /// Append value to the path-like variable
+    ///
+    /// See `env_separator`
+    pub fn append(&mut self, source: impl ToString, name: impl ToString, value: impl ToString) {
+        self.push(source, name, value, EnvMode::Append);
+    }
+
+    /// Set variable, or append its value if the name ends with `+`
+    pub fn add(&mut self, source: impl ToString, name: impl AsRef<str>, value: impl ToString) {
+        let name = name.as_ref();
+
+        match name.strip_suffix('+') {
+            Some(name) => self.append(source, name.trim_end(), value),
+            None => self.set(source, name, value)
+        }
+    }
+
+    /// Get final value of the variable
+    pub fn get(&self, name: impl AsRef<str>) -> Option<String> {
+        let name = name.as_ref();
+
+        self.vars.get(name).map(|layers| resolve_value(name, layers))
+    }
+
+    /// Get all the values set for the variable, in order of their layers
+    pub fn layers(&self, name: impl AsRef<str>) -> &[EnvValue] {
+        self.vars.get(name.as_ref())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.vars.is_empty()
+    }
+
+    /// Get final values of all the variables
+    pub fn resolve(&self) -> HashMap<String, String> {
+        self.vars.iter()
+            .map(|(name, layers)| (name.clone(), resolve_value(name, layers)))
+            .collect()
+    }
+
+    /// Apply function to every value of every layer
+    pub fn map_values(&self, mut f: impl FnMut(&str) -> String) -> Self {
+        let vars = self.vars.iter()
+            .map(|(name, layers)| {
+                let layers = layers.iter()
+                    .map(|layer| EnvValue {
+                        value: f(&layer.value),
+                        ..layer.clone()
+                    })
+                    .collect();
+
+                (name.clone(), layers)
+            })
+            .collect();
+
+        Self { vars }
+    }
+
+    /// Get report about every variable, sorted by their names
+    pub fn report(&self) -> Vec<ResolvedEnv> {
+        let mut report = self.vars.iter()
+            .map(|(name, layers)| ResolvedEnv {
+                name: name.clone(),
+                value: resolve_value(name, layers),
+                inherited: inherited_value(name, layers),
+                source: layers.last()
+                    .map(|layer| layer.source.clone())
+                    .unwrap_or_default(),
+                layers: layers.clone()
+            })
+            .collect::<Vec<_>>();
+
+        report.sort_by(|a, b| a.name.cmp(&b.name));
+
+        report
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Final value of the environment variable
+pub struct ResolvedEnv {
+    pub name: String,
+    pub value: String,
+
+    /// Name of the setting which has set this variable last
+    pub source: String,
+
+    /// Value of the host variable extended by the appended layers
+    pub inherited: Option<String>,
+
+    /// All the values set for this variable, in order of their layers
+    pub layers: Vec<EnvValue>
+}
+
+impl ResolvedEnv {
+    /// Index of the layer which has set the value last time
+    fn last_set(&self) -> usize {
+        self.layers.iter()
+            .rposition(|layer| layer.mode == EnvMode::Set)
+            .unwrap_or(0)
+    }
+
+    /// Get layers which values were discarded by the later layers
+    pub fn overridden(&self) -> &[EnvValue] {
+        &self.layers[..self.last_set()]
+    }
+
+    /// Get layers which make up the final value
+    pub fn applied(&self) -> &[EnvValue] {
+        &self.layers[self.last_set()..]
+    }
+
+    /// Explain why the variable has its final value
+    pub fn explain(&self) -> String {
+        let mut lines = vec![format!("{}={}", self.name, self.value)];
+
+        let overriding = self.applied()
+            .first()
+            .map(|layer| layer.source.as_str())
+            .unwrap_or_default();
+
+        if let Some(inherited) = &self.inherited {
+            lines.push(format!("  inherited from the host: {inherited}"));
+        }
+
+        for layer in self.overridden() {
+            lines.push(format!("  overridden by {overriding}: {} ({})", layer.value, layer.source));
+        }
+
+        for layer in self.applied() {
+            let action = match layer.mode {
+                EnvMode::Set => "set",
+                EnvMode::Append => "appended"
+            };
+
+            lines.push(format!("  {action} by {}: {}", layer.source, layer.value));
+        }
+
+        lines.join("\n")
+    }
+}
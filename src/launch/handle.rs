@@ -0,0 +1,584 @@
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus};
+use std::fs::File;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{Receiver, SyncSender, TryIter};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime};
+
+use super::process::{self, ProcessTree};
+use super::outcome::{RunOutcome, ProcessTreeEnd};
+use super::logs::format_time;
+use super::hooks::{HookRunner, HookStage};
+use super::services::{self, RunningService};
+
+/// Max number of log lines stored until the frontend reads them
+const LOG_LINES_BUFFER: usize = 4096;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Wineserver of the wine prefix used by the game
+pub struct Wineserver {
+    /// Path to the wineserver binary
+    pub binary: PathBuf,
+
+    /// Path to the wine prefix on the host system
+    pub prefix: PathBuf
+}
+
+impl Wineserver {
+    /// Kill all the processes running in the wine prefix
+    pub fn kill(&self) -> anyhow::Result<()> {
+        let status = Command::new(&self.binary)
+            .arg("-k")
+            .env("WINEPREFIX", &self.prefix)
+            .status()?;
+
+        if !status.success() {
+            anyhow::bail!("Failed to kill wineserver: {status}");
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Max time the game is allowed to run
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use anime_launcher_sdk::launch::*;
+///
+/// let mut plan = LaunchPlan::new("/");
+///
+/// plan.runner.push(String::from("sleep"));
+/// plan.launch_args.push(String::from("30"));
+///
+/// plan.time_limit = Some(TimeLimit {
+///     playtime: Duration::from_secs(1),
+///     warning: Duration::from_secs(1)
+/// });
+///
+/// let log_file = std::env::temp_dir().join("anime-launcher-sdk-time-limit-test.log");
+///
+/// let handle = plan.spawn(&log_file, 1024, |_| Ok(())).unwrap();
+///
+/// assert!(handle.remaining_playtime().unwrap() <= Duration::from_secs(1));
+///
+/// let outcome = handle.wait().unwrap();
+///
+/// assert!(outcome.time_limit_reached);
+/// assert_eq!(outcome.tree_end, ProcessTreeEnd::Killed);
+/// assert!(outcome.duration < Duration::from_secs(10));
+/// ```
+pub struct TimeLimit {
+    /// Time after which the game is stopped
+    pub playtime: Duration,
+
+    /// Run playtime warning hooks this amount of time
+    /// before the game is stopped
+    pub warning: Duration
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogStream {
+    Stdout,
+    Stderr
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Line printed by the game process
+pub struct LogLine {
+    pub stream: LogStream,
+    pub line: String,
+
+    /// Time when the line was printed
+    pub time: SystemTime
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameStatus {
+    /// Game processes are still running
+    Running,
+
+    /// Game processes have exited and post-exit steps are running
+    Finishing,
+
+    /// Game processes have exited and post-exit steps are finished.
+    /// `GameHandle::wait` won't block anymore
+    Exited
+}
+
+#[derive(Debug, Default)]
+struct SharedState {
+    /// Exit status of the spawned command
+    pub exit_status: Option<ExitStatus>,
+
+    /// Running game processes
+    pub processes: Option<ProcessTree>,
+
+    /// All the game processes have exited
+    pub exited: bool,
+
+    /// Game was stopped using `GameHandle::kill`
+    pub killed: bool,
+
+    /// Game was stopped because of the time limit
+    pub time_limit_reached: bool
+}
+
+/// Handle of the running game
+///
+/// Returned by `LaunchPlan::spawn`. Game processes are watched
+/// in a background thread which also runs post-exit steps
+/// when the game is closed. Session outcome is returned by `wait`
+pub struct GameHandle {
+    pid: u32,
+    lines: Receiver<LogLine>,
+    state: Arc<Mutex<SharedState>>,
+    watcher: JoinHandle<anyhow::Result<RunOutcome>>,
+    wineserver: Option<Wineserver>,
+    deadline: Option<Instant>
+}
+
+impl GameHandle {
+    /// Start watching the spawned game process
+    ///
+    /// `services` are stopped when all the game processes have exited.
+    ///
+    /// `on_exit` is called in the watcher thread after that, when the game
+    /// log file is closed. It can add suggestions to the session outcome.
+    /// Post-exit hooks are executed after it
+    pub(crate) fn new(
+        mut child: Child,
+        log_file: File,
+        log_limit: usize,
+        wineserver: Option<Wineserver>,
+        services: Vec<RunningService>,
+        hooks: HookRunner,
+        on_exit: impl FnOnce(&mut RunOutcome) -> anyhow::Result<()> + Send + 'static
+    ) -> Self {
+        let pid = child.id();
+
+        let start_time = Instant::now();
+
+        let (sender, lines) = std::sync::mpsc::sync_channel(LOG_LINES_BUFFER);
+
+        let state = Arc::new(Mutex::new(SharedState {
+            processes: Some(ProcessTree::new(pid)),
+            ..SharedState::default()
+        }));
+
+        let game_output = Arc::new(Mutex::new(log_file));
+
+        let limit = Arc::new(LogLimit {
+            limit: log_limit,
+            written: AtomicUsize::new(0),
+            reached: AtomicBool::new(false)
+        });
+
+        let watcher_limit = limit.clone();
+
+        let stdout_join = child.stdout.take()
+            .map(|stdout| redirect_output(stdout, LogStream::Stdout, game_output.clone(), limit.clone(), sender.clone()));
+
+        let stderr_join = child.stderr.take()
+            .map(|stderr| redirect_output(stderr, LogStream::Stderr, game_output.clone(), limit, sender));
+
+        let watcher_state = state.clone();
+
+        let watcher = std::thread::spawn(move || -> anyhow::Result<RunOutcome> {
+            // Services are stopped and post-exit hooks are executed
+            // even if some of the steps below has failed
+            let mut cleanup = ExitCleanup {
+                services,
+                hooks: hooks.clone()
+            };
+
+            let mut processes = ProcessTree::new(pid);
+
+            let mut detached = false;
+
+            // Wine starts windows processes in background, so we have to wait
+            // for all of them, not only for the spawned command
+            let exit_status = loop {
+                let exit_status = child.try_wait()?;
+
+                if let Some(status) = exit_status {
+                    processes.update();
+
+                    if processes.is_empty() {
+                        break status;
+                    }
+
+                    detached = true;
+                }
+
+                if let Ok(mut state) = watcher_state.lock() {
+                    state.exit_status = exit_status;
+                    state.processes = Some(processes.clone());
+                }
+
+                std::thread::sleep(std::time::Duration::from_millis(500));
+
+                processes.update();
+            };
+
+            let duration = start_time.elapsed();
+
+            tracing::info!("Game processes have exited");
+
+            cleanup.stop_services();
+
+            let mut killed = false;
+            let mut time_limit_reached = false;
+
+            if let Ok(mut state) = watcher_state.lock() {
+                state.exit_status = Some(exit_status);
+                state.processes = None;
+                state.exited = true;
+
+                killed = state.killed;
+                time_limit_reached = state.time_limit_reached;
+            }
+
+            // Flush and close the game log file
+            if let Ok(mut file) = game_output.lock() {
+                file.flush()?;
+            }
+
+            drop(game_output);
+
+            if let Some(join) = stdout_join {
+                join.join()
+                    .map_err(|err| anyhow::anyhow!("Failed to join stdout reader thread: {err:?}"))??;
+            }
+
+            if let Some(join) = stderr_join {
+                join.join()
+                    .map_err(|err| anyhow::anyhow!("Failed to join stderr reader thread: {err:?}"))??;
+            }
+
+            let tree_end = if killed {
+                ProcessTreeEnd::Killed
+            } else if detached {
+                ProcessTreeEnd::Detached
+            } else {
+                ProcessTreeEnd::Exited
+            };
+
+            let log_limit_reached = watcher_limit.reached.load(Ordering::Relaxed);
+
+            let mut outcome = RunOutcome::new(exit_status, duration, tree_end, log_limit_reached);
+
+            outcome.time_limit_reached = time_limit_reached;
+
+            on_exit(&mut outcome)?;
+
+            // Run post-exit hooks
+            drop(cleanup);
+
+            outcome.hook_failures = hooks.take_failures();
+
+            Ok(outcome)
+        });
+
+        Self {
+            pid,
+            lines,
+            state,
+            watcher,
+            wineserver,
+            deadline: None
+        }
+    }
+
+    /// Stop the game when the time limit is reached
+    ///
+    /// Playtime warning hooks are started in background
+    /// when the game is about to be stopped
+    pub(crate) fn watch_time_limit(&mut self, limit: TimeLimit, hooks: HookRunner) {
+        let start_time = Instant::now();
+
+        self.deadline = Some(start_time + limit.playtime);
+
+        let state = self.state.clone();
+        let wineserver = self.wineserver.clone();
+
+        std::thread::spawn(move || {
+            let warning_time = limit.playtime.saturating_sub(limit.warning);
+
+            let mut warned = false;
+
+            loop {
+                if state.lock().map(|state| state.exited).unwrap_or(true) {
+                    break;
+                }
+
+                let elapsed = start_time.elapsed();
+
+                if elapsed >= limit.playtime {
+                    tracing::warn!("Playtime limit is reached");
+
+                    if let Ok(mut state) = state.lock() {
+                        state.time_limit_reached = true;
+                    }
+
+                    if let Err(err) = stop_game(&state, wineserver.as_ref()) {
+                        tracing::error!("Failed to stop the game: {err}");
+                    }
+
+                    break;
+                }
+
+                if !warned && elapsed >= warning_time {
+                    tracing::warn!("Game will be stopped in {} seconds because of the playtime limit", (limit.playtime - elapsed).as_secs());
+
+                    // Blocking hooks must not delay the game stop
+                    let hooks = hooks.clone();
+
+                    std::thread::spawn(move || hooks.run(HookStage::PlaytimeWarning));
+
+                    warned = true;
+                }
+
+                std::thread::sleep(Duration::from_millis(500));
+            }
+        });
+    }
+
+    #[inline]
+    /// PID of the spawned command
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// PIDs of the running game processes
+    pub fn pids(&self) -> HashSet<u32> {
+        self.state.lock()
+            .ok()
+            .and_then(|state| Some(state.processes.as_ref()?.pids()))
+            .unwrap_or_default()
+    }
+
+    /// Exit status of the spawned command
+    ///
+    /// Note that windows processes started by the command
+    /// can still be running when it's available
+    pub fn exit_status(&self) -> Option<ExitStatus> {
+        self.state.lock().ok()
+            .and_then(|state| state.exit_status)
+    }
+
+    /// Get current game status
+    pub fn status(&self) -> GameStatus {
+        if self.watcher.is_finished() {
+            return GameStatus::Exited;
+        }
+
+        match self.state.lock() {
+            Ok(state) if state.exited => GameStatus::Finishing,
+
+            _ => GameStatus::Running
+        }
+    }
+
+    #[inline]
+    /// Check if game processes are still running
+    pub fn is_running(&self) -> bool {
+        self.status() == GameStatus::Running
+    }
+
+    #[inline]
+    /// Get game output lines printed since the last call
+    ///
+    /// Lines are dropped if they're not read for too long
+    pub fn lines(&self) -> TryIter<'_, LogLine> {
+        self.lines.try_iter()
+    }
+
+    /// Get time left until the game is stopped by the time limit
+    ///
+    /// Returns `None` if the game has no time limit
+    pub fn remaining_playtime(&self) -> Option<Duration> {
+        self.deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    #[inline]
+    /// Stop the game
+    ///
+    /// Kills the wineserver of the used prefix and sends `SIGTERM`
+    /// to the remaining game processes. Use `wait` to wait until
+    /// they're closed and post-exit steps are finished
+    pub fn kill(&self) -> anyhow::Result<()> {
+        stop_game(&self.state, self.wineserver.as_ref())
+    }
+
+    /// Wait until the game is closed and post-exit steps are finished
+    pub fn wait(self) -> anyhow::Result<RunOutcome> {
+        self.watcher.join()
+            .map_err(|err| anyhow::anyhow!("Failed to join game watcher thread: {err:?}"))?
+    }
+}
+
+impl std::fmt::Debug for GameHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GameHandle")
+            .field("pid", &self.pid)
+            .field("wineserver", &self.wineserver)
+            .finish()
+    }
+}
+
+fn stop_game(state: &Mutex<SharedState>, wineserver: Option<&Wineserver>) -> anyhow::Result<()> {
+    tracing::info!("Stopping the game");
+
+    if let Ok(mut state) = state.lock() {
+        state.killed = true;
+    }
+
+    if let Some(wineserver) = wineserver {
+        if let Err(err) = wineserver.kill() {
+            tracing::warn!("Failed to stop wineserver: {err}");
+        }
+    }
+
+    let Some(processes) = state.lock().ok().and_then(|state| state.processes.clone())
+    else {
+        return Ok(());
+    };
+
+    // Signal the whole game process group at once while it still exists,
+    // so the processes spawned in the meantime are stopped too
+    let mut group_signalled = false;
+
+    if processes.pids().iter().any(|pid| processes.is_group_member(*pid)) {
+        match process::send_group_signal(processes.group(), libc::SIGTERM) {
+            Ok(()) => group_signalled = true,
+            Err(err) => tracing::warn!("Failed to send SIGTERM to the game process group {}: {err}", processes.group())
+        }
+    }
+
+    // Then the processes which have left the group. PIDs are checked again
+    // right before signalling since they could be reused by other processes
+    for pid in processes.pids() {
+        if group_signalled && processes.is_group_member(pid) {
+            continue;
+        }
+
+        if !processes.contains_process(pid) {
+            tracing::debug!(pid, "Skipping process which doesn't belong to the game anymore");
+
+            continue;
+        }
+
+        if let Err(err) = process::send_signal(pid, libc::SIGTERM) {
+            tracing::warn!("Failed to send SIGTERM to the game process {pid}: {err}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Post-exit steps which are executed however the watcher has finished
+struct ExitCleanup {
+    services: Vec<RunningService>,
+    hooks: HookRunner
+}
+
+impl ExitCleanup {
+    #[inline]
+    fn stop_services(&mut self) {
+        services::stop_services(std::mem::take(&mut self.services));
+    }
+}
+
+impl Drop for ExitCleanup {
+    fn drop(&mut self) {
+        self.stop_services();
+
+        self.hooks.run(HookStage::PostExit);
+    }
+}
+
+#[derive(Debug)]
+struct LogLimit {
+    /// Max size of the log file in bytes
+    pub limit: usize,
+
+    /// Amount of bytes written to the log file
+    pub written: AtomicUsize,
+
+    /// Log file has reached its size limit
+    pub reached: AtomicBool
+}
+
+/// Redirect output of the game process to the log file in a separate thread
+///
+/// Every line is prefixed with the time it was printed at:
+///
+/// ```text
+/// [2026-10-17 12:34:56.789] [stdout] some game output
+/// [2026-10-17 12:34:56.790] [stderr] some game error
+/// ```
+fn redirect_output(
+    output: impl Read + Send + 'static,
+    stream: LogStream,
+    game_output: Arc<Mutex<File>>,
+    limit: Arc<LogLimit>,
+    lines: SyncSender<LogLine>
+) -> JoinHandle<std::io::Result<()>> {
+    std::thread::spawn(move || -> std::io::Result<()> {
+        let prefix = match stream {
+            LogStream::Stdout => "[stdout]",
+            LogStream::Stderr => "[stderr]"
+        };
+
+        let mut output = BufReader::new(output);
+        let mut line = Vec::new();
+
+        // Keep reading the output even when the log limit is reached
+        // so the game is not blocked on writing to the full pipe
+        while let Ok(read) = output.read_until(b'\n', &mut line) {
+            if read == 0 {
+                break;
+            }
+
+            if line.ends_with(b"\n") {
+                line.pop();
+            }
+
+            let time = SystemTime::now();
+            let line = String::from_utf8_lossy(&std::mem::take(&mut line)).to_string();
+
+            if !limit.reached.load(Ordering::Relaxed) {
+                if let Ok(mut game_output) = game_output.lock() {
+                    let entry = format!("[{}] {prefix} {line}\n", format_time(time));
+
+                    let written = limit.written.fetch_add(entry.len(), Ordering::Relaxed) + entry.len();
+
+                    if written <= limit.limit {
+                        game_output.write_all(entry.as_bytes())?;
+                    }
+
+                    // Tell that the rest of output is not saved
+                    else if !limit.reached.swap(true, Ordering::Relaxed) {
+                        game_output.write_all(format!("[{}] Log file size limit is reached, the rest of the output is not saved\n", format_time(time)).as_bytes())?;
+                    }
+                }
+            }
+
+            // Lines are dropped if the frontend doesn't read them
+            let _ = lines.try_send(LogLine {
+                stream,
+                line,
+                time
+            });
+        }
+
+        Ok(())
+    })
+}
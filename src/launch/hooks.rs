@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::os::unix::process::CommandExt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config::schema_blanks::hooks::Hook;
+
+use super::keywords::Keywords;
+use super::process;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HookStage {
+    /// Hook is executed before the game launch
+    PreLaunch,
+
+    /// Hook is executed after all the game processes have exited
+    PostExit,
+
+    /// Hook is executed when the game is going to be stopped
+    /// because of the playtime limits
+    PlaytimeWarning
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Hook command prepared for execution
+pub struct LaunchHook {
+    pub stage: HookStage,
+
+    /// Shell command with already replaced keywords
+    pub command: String,
+
+    /// Wait until the hook finishes
+    pub blocking: bool,
+
+    /// Kill the hook if it runs longer than this
+    pub timeout: Option<Duration>
+}
+
+impl LaunchHook {
+    /// Prepare hook from the config
+    ///
+    /// Keywords are replaced the same way as in the launch command template
+    ///
+    /// ```
+    /// use anime_launcher_sdk::config::schema_blanks::hooks::Hook;
+    /// use anime_launcher_sdk::launch::hooks::{LaunchHook, HookStage};
+    /// use anime_launcher_sdk::launch::keywords::Keywords;
+    ///
+    /// let hook = Hook {
+    ///     command: String::from("backup.sh %prefix%"),
+    ///     ..Hook::default()
+    /// };
+    ///
+    /// let keywords = Keywords::new().with("prefix", "/games/it's prefix");
+    ///
+    /// let hook = LaunchHook::from_config(HookStage::PreLaunch, &hook, &keywords).unwrap();
+    ///
+    /// assert_eq!(hook.command, "backup.sh '/games/it'\\''s prefix'");
+    /// assert_eq!(hook.timeout, Some(std::time::Duration::from_secs(60)));
+    /// ```
+    pub fn from_config(stage: HookStage, hook: &Hook, keywords: &Keywords) -> anyhow::Result<Self> {
+        Ok(Self {
+            stage,
+            command: keywords.substitute(&hook.command, &[])?,
+            blocking: hook.blocking,
+            timeout: (hook.timeout > 0).then(|| Duration::from_secs(hook.timeout))
+        })
+    }
+
+    fn spawn(&self, env: &HashMap<String, String>, current_dir: &Path) -> std::io::Result<Child> {
+        Command::new("bash")
+            .arg("-c")
+            .arg(&self.command)
+            .envs(env)
+            .current_dir(current_dir)
+            .stdin(Stdio::null())
+            .process_group(0)
+            .spawn()
+    }
+
+    /// Run the hook and wait until it finishes or its timeout is reached
+    ///
+    /// Hook is started in its own process group which is killed
+    /// together with the processes spawned by the hook on timeout
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use std::time::Duration;
+    ///
+    /// use anime_launcher_sdk::launch::hooks::{LaunchHook, HookStage};
+    ///
+    /// let marker = std::env::temp_dir().join(format!("hook-marker-{}", std::process::id()));
+    ///
+    /// let hook = LaunchHook {
+    ///     stage: HookStage::PostExit,
+    ///     command: format!("(sleep 2; touch '{}') & sleep 30", marker.display()),
+    ///     blocking: true,
+    ///     timeout: Some(Duration::from_secs(1))
+    /// };
+    ///
+    /// assert!(hook.run(&HashMap::new(), std::path::Path::new("/")).is_err());
+    ///
+    /// // Background process of the hook was killed as well
+    /// std::thread::sleep(Duration::from_secs(2));
+    ///
+    /// assert!(!marker.exists());
+    /// ```
+    pub fn run(&self, env: &HashMap<String, String>, current_dir: &Path) -> anyhow::Result<()> {
+        let mut child = self.spawn(env, current_dir)?;
+
+        let start = Instant::now();
+
+        loop {
+            if let Some(status) = child.try_wait()? {
+                if !status.success() {
+                    anyhow::bail!("Hook has exited with {status}");
+                }
+
+                return Ok(());
+            }
+
+            if let Some(timeout) = self.timeout {
+                if start.elapsed() >= timeout {
+                    process::send_group_signal(child.id(), libc::SIGKILL)?;
+
+                    child.wait()?;
+
+                    anyhow::bail!("Hook was killed after {} seconds timeout", timeout.as_secs());
+                }
+            }
+
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Hook which has failed or reached its timeout
+pub struct HookFailure {
+    pub stage: HookStage,
+    pub command: String,
+    pub error: String
+}
+
+#[derive(Debug, Clone)]
+/// Runner of the game hooks
+///
+/// Failures of the background hooks are collected
+/// while they're running
+///
+/// ```
+/// use std::collections::HashMap;
+/// use std::time::Duration;
+///
+/// use anime_launcher_sdk::launch::hooks::*;
+///
+/// let hook = |command: &str, blocking| LaunchHook {
+///     stage: HookStage::PreLaunch,
+///     command: command.to_string(),
+///     blocking,
+///     timeout: Some(Duration::from_secs(1))
+/// };
+///
+/// let hooks = HookRunner::new(vec![
+///     hook("test \"$HOOK_TEST\" = 1", true),
+///     hook("exit 3", true),
+///     hook("sleep 5", false)
+/// ], HashMap::from([(String::from("HOOK_TEST"), String::from("1"))]), "/");
+///
+/// hooks.run(HookStage::PreLaunch);
+///
+/// std::thread::sleep(Duration::from_secs(2));
+///
+/// let failures = hooks.take_failures();
+///
+/// assert_eq!(failures.len(), 2);
+/// assert_eq!(failures[0].command, "exit 3");
+/// assert_eq!(failures[1].command, "sleep 5");
+/// ```
+pub struct HookRunner {
+    hooks: Vec<LaunchHook>,
+    env: HashMap<String, String>,
+    current_dir: PathBuf,
+    failures: Arc<Mutex<Vec<HookFailure>>>
+}
+
+impl HookRunner {
+    #[inline]
+    pub fn new(hooks: Vec<LaunchHook>, env: HashMap<String, String>, current_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            hooks,
+            env,
+            current_dir: current_dir.into(),
+            failures: Arc::new(Mutex::new(Vec::new()))
+        }
+    }
+
+    /// Run hooks of the given stage
+    ///
+    /// Blocking hooks are executed one by one, background hooks
+    /// are started in separate threads
+    pub fn run(&self, stage: HookStage) {
+        for hook in self.hooks.iter().filter(|hook| hook.stage == stage) {
+            tracing::info!("Running {stage:?} hook: {}", hook.command);
+
+            if hook.blocking {
+                if let Err(err) = hook.run(&self.env, &self.current_dir) {
+                    self.add_failure(hook, err);
+                }
+            }
+
+            else {
+                let hooks = self.clone();
+                let hook = hook.clone();
+
+                std::thread::spawn(move || {
+                    if let Err(err) = hook.run(&hooks.env, &hooks.current_dir) {
+                        hooks.add_failure(&hook, err);
+                    }
+                });
+            }
+        }
+    }
+
+    fn add_failure(&self, hook: &LaunchHook, err: anyhow::Error) {
+        tracing::warn!("{:?} hook has failed: {err}", hook.stage);
+
+        if let Ok(mut failures) = self.failures.lock() {
+            failures.push(HookFailure {
+                stage: hook.stage,
+                command: hook.command.clone(),
+                error: err.to_string()
+            });
+        }
+    }
+
+    /// Take failures of the finished hooks
+    pub fn take_failures(&self) -> Vec<HookFailure> {
+        self.failures.lock()
+            .map(|mut failures| std::mem::take(&mut *failures))
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,310 @@
+//! Keywords substitution in the launch commands and environment values
+//!
+//! Keywords are written as `%name%`, where the name consists of
+//! ASCII letters, digits and underscores. Literal `%` can be escaped
+//! as `%%`. Other `%` symbols which don't start a keyword are kept as is.
+//! Unknown keywords are errors in the components commands, while in the user
+//! templates and environment values they're kept as is with a warning,
+//! so templates written before the keywords were added keep working.
+//!
+//! Keywords set by the games:
+//!
+//! | Keyword          | Value                                      |
+//! | ---------------- | ------------------------------------------ |
+//! | `%build%`        | Path to the selected wine build            |
+//! | `%prefix%`       | Path to the wine prefix                    |
+//! | `%game%`         | Path to the game folder                    |
+//! | `%temp%`         | Path to the temp folder                    |
+//! | `%launcher%`     | Path to the launcher folder                |
+//! | `%patch%`        | Path to the game patch, if the game has it |
+//! | `%wine_name%`    | Name of the selected wine build            |
+//! | `%dxvk_version%` | Version of the installed DXVK, or empty    |
+//! | `%edition%`      | Game edition                               |
+//! | `%game_version%` | Installed game version, or empty           |
+//! | `%exe%`          | Name of the game executable                |
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    Text(&'a str),
+    Keyword(&'a str)
+}
+
+/// Split text into literal parts and keywords
+fn tokenize(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+
+    let mut start = 0;
+    let mut i = 0;
+
+    while let Some(offset) = text[i..].find('%') {
+        let percent = i + offset;
+        let rest = &text[percent + 1..];
+
+        // Escaped "%%"
+        if rest.starts_with('%') {
+            tokens.push(Token::Text(&text[start..=percent]));
+
+            i = percent + 2;
+            start = i;
+
+            continue;
+        }
+
+        let name_len = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+
+        if name_len > 0 && rest[name_len..].starts_with('%') {
+            tokens.push(Token::Text(&text[start..percent]));
+            tokens.push(Token::Keyword(&rest[..name_len]));
+
+            i = percent + name_len + 2;
+            start = i;
+        }
+
+        else {
+            i = percent + 1;
+        }
+    }
+
+    tokens.push(Token::Text(&text[start..]));
+
+    tokens.retain(|token| token != &Token::Text(""));
+
+    tokens
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Context {
+    Unquoted,
+    SingleQuoted,
+    DoubleQuoted
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Set of keywords values
+///
+/// ```
+/// use anime_launcher_sdk::launch::keywords::Keywords;
+///
+/// let keywords = Keywords::new()
+///     .with("game", "/games/game")
+///     .with("temp", "/tmp");
+///
+/// assert_eq!(keywords.replace("%game%/file %temp%/file").unwrap(), "/games/game/file /tmp/file");
+/// assert_eq!(keywords.replace("100%% %%game%% 50% %").unwrap(), "100% %game% 50% %");
+///
+/// assert!(keywords.replace("%gmae%").is_err());
+/// ```
+pub struct Keywords {
+    values: Vec<(String, String)>
+}
+
+impl Keywords {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set value of the keyword
+    ///
+    /// `name` is the keyword without `%` symbols
+    pub fn insert(&mut self, name: impl ToString, value: impl ToString) {
+        let name = name.to_string();
+        let value = value.to_string();
+
+        match self.values.iter_mut().find(|(known, _)| known == &name) {
+            Some((_, known)) => *known = value,
+            None => self.values.push((name, value))
+        }
+    }
+
+    #[inline]
+    /// Set value of the keyword
+    ///
+    /// `name` is the keyword without `%` symbols
+    pub fn with(mut self, name: impl ToString, value: impl ToString) -> Self {
+        self.insert(name, value);
+
+        self
+    }
+
+    #[inline]
+    pub fn get(&self, name: impl AsRef<str>) -> Option<&str> {
+        let name = name.as_ref();
+
+        self.values.iter()
+            .find(|(known, _)| known == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values.iter().map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Replace keywords in the text with their values
+    ///
+    /// Unknown keywords are reported as errors
+    pub fn replace(&self, text: impl AsRef<str>) -> anyhow::Result<String> {
+        let mut result = String::new();
+
+        for token in tokenize(text.as_ref()) {
+            match token {
+                Token::Text(text) => result += text,
+
+                Token::Keyword(name) => match self.get(name) {
+                    Some(value) => result += value,
+                    None => anyhow::bail!("Unknown keyword: %{name}%")
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Replace keywords in the environment variable value
+    ///
+    /// Unlike `replace` unknown keywords are kept as is, because values
+    /// of the variables can contain `%` symbols of their own
+    ///
+    /// ```
+    /// use anime_launcher_sdk::launch::keywords::Keywords;
+    ///
+    /// let keywords = Keywords::new().with("prefix", "/games/prefix");
+    ///
+    /// assert_eq!(keywords.replace_env("%prefix%/drive_c"), "/games/prefix/drive_c");
+    /// assert_eq!(keywords.replace_env("%APPDATA%;%prefix%"), "%APPDATA%;/games/prefix");
+    /// ```
+    pub fn replace_env(&self, value: impl AsRef<str>) -> String {
+        let mut result = String::new();
+
+        for token in tokenize(value.as_ref()) {
+            match token {
+                Token::Text(text) => result += text,
+
+                Token::Keyword(name) => match self.get(name) {
+                    Some(value) => result += value,
+
+                    None => {
+                        tracing::warn!("Unknown keyword %{name}% is kept in the environment value");
+
+                        result += "%";
+                        result += name;
+                        result += "%";
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Replace keywords in the shell command
+    ///
+    /// Every keyword value is escaped according to the place where
+    /// the keyword is used: outside of quotes, inside of single quotes
+    /// or inside of double quotes, so the value always ends up as one piece
+    /// of a shell word. Values from `raw` are shell code themselves
+    /// and are pasted as is
+    ///
+    /// Unknown keywords are kept as is, like in `replace_env`
+    ///
+    /// ```
+    /// use anime_launcher_sdk::launch::keywords::Keywords;
+    ///
+    /// let keywords = Keywords::new().with("game", "/games/it's \"$HOME\" 100%");
+    ///
+    /// let raw = [("command", "printf '%s\\n'")];
+    ///
+    /// for template in ["%command% %game%", "%command% '%game%'", "%command% \"%game%\"", "%command% %game%/x"] {
+    ///     let command = keywords.substitute(template, &raw).unwrap();
+    ///
+    ///     let output = std::process::Command::new("bash")
+    ///         .arg("-c")
+    ///         .arg(&command)
+    ///         .output()
+    ///         .unwrap();
+    ///
+    ///     assert_eq!(
+    ///         String::from_utf8_lossy(&output.stdout).trim_end(),
+    ///         template.replace("%command% ", "").replace(['\'', '"'], "").replace("%game%", keywords.get("game").unwrap())
+    ///     );
+    /// }
+    ///
+    /// assert_eq!(keywords.substitute("date +%%H%%M %command%", &raw).unwrap(), "date +%H%M printf '%s\\n'");
+    /// assert_eq!(keywords.substitute("%command% %unknown%", &raw).unwrap(), "printf '%s\\n' %unknown%");
+    /// ```
+    pub fn substitute(&self, command: impl AsRef<str>, raw: &[(&str, &str)]) -> anyhow::Result<String> {
+        let mut result = String::new();
+
+        let mut context = Context::Unquoted;
+        let mut escaped = false;
+
+        for token in tokenize(command.as_ref()) {
+            match token {
+                Token::Text(text) => {
+                    for c in text.chars() {
+                        result.push(c);
+
+                        // Escaped symbol is copied as is
+                        if escaped {
+                            escaped = false;
+
+                            continue;
+                        }
+
+                        match (context, c) {
+                            (Context::Unquoted, '\'') => context = Context::SingleQuoted,
+                            (Context::Unquoted, '"') => context = Context::DoubleQuoted,
+
+                            (Context::SingleQuoted, '\'') |
+                            (Context::DoubleQuoted, '"') => context = Context::Unquoted,
+
+                            (Context::Unquoted | Context::DoubleQuoted, '\\') => escaped = true,
+
+                            _ => ()
+                        }
+                    }
+                }
+
+                Token::Keyword(name) => {
+                    escaped = false;
+
+                    if let Some((_, value)) = raw.iter().find(|(keyword, _)| *keyword == name) {
+                        result += value;
+
+                        continue;
+                    }
+
+                    let Some(value) = self.get(name)
+                    else {
+                        tracing::warn!("Unknown keyword %{name}% is kept in the command");
+
+                        result += "%";
+                        result += name;
+                        result += "%";
+
+                        continue;
+                    };
+
+                    match context {
+                        Context::Unquoted => result += &crate::shell::quote(value),
+                        Context::SingleQuoted => result += &value.replace('\'', "'\\''"),
+
+                        Context::DoubleQuoted => {
+                            for c in value.chars() {
+                                if matches!(c, '"' | '\\' | '$' | '`') {
+                                    result.push('\\');
+                                }
+
+                                result.push(c);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
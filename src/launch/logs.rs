@@ -0,0 +1,401 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Extension of the compressed logs
+pub const COMPRESSED_EXTENSION: &str = "zst";
+
+/// Format given time as `YYYY-MM-DD HH:MM:SS.mmm` in UTC
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// use anime_launcher_sdk::launch::logs::format_time;
+///
+/// assert_eq!(format_time(UNIX_EPOCH), "1970-01-01 00:00:00.000");
+/// assert_eq!(format_time(UNIX_EPOCH + Duration::from_millis(1_760_703_296_789)), "2025-10-17 12:14:56.789");
+/// ```
+pub fn format_time(time: SystemTime) -> String {
+    let time = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+
+    let secs = time.as_secs();
+    let days = (secs / 86400) as i64;
+
+    // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}.{:03}",
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60,
+        time.subsec_millis()
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Log file of a single game session
+pub struct SessionLog {
+    pub path: PathBuf,
+
+    /// Time when the session was started
+    pub started_at: SystemTime,
+
+    /// Log file is compressed with zstd
+    pub compressed: bool
+}
+
+impl SessionLog {
+    fn from_path(path: PathBuf) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+
+        let compressed = name.ends_with(&format!(".log.{COMPRESSED_EXTENSION}"));
+
+        if !name.starts_with("game-") || !(name.ends_with(".log") || compressed) {
+            return None;
+        }
+
+        let started_at = path.metadata()
+            .and_then(|metadata| metadata.created().or(metadata.modified()))
+            .ok()?;
+
+        Some(Self {
+            path,
+            started_at,
+            compressed
+        })
+    }
+
+    /// Open the log file for reading
+    ///
+    /// Compressed logs are decompressed on the fly
+    pub fn open(&self) -> anyhow::Result<Box<dyn Read + Send>> {
+        let file = File::open(&self.path)?;
+
+        if self.compressed {
+            decompress(file)
+        }
+
+        else {
+            Ok(Box::new(file))
+        }
+    }
+
+    /// Read the whole log file
+    pub fn read(&self) -> anyhow::Result<String> {
+        let mut log = String::new();
+
+        self.open()?.read_to_string(&mut log)?;
+
+        Ok(log)
+    }
+
+    /// Follow the log file, like `tail -f` does
+    ///
+    /// Can't be used with compressed logs
+    pub fn follow(&self) -> anyhow::Result<LogFollower> {
+        if self.compressed {
+            anyhow::bail!("Compressed logs can't be followed");
+        }
+
+        Ok(LogFollower {
+            reader: BufReader::new(File::open(&self.path)?),
+            line: String::new()
+        })
+    }
+
+    /// Make a symlink to the current log file
+    ///
+    /// Existing file at the given path is replaced
+    pub fn link_to(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+
+        if path.symlink_metadata().is_ok() {
+            std::fs::remove_file(path)?;
+        }
+
+        std::os::unix::fs::symlink(&self.path, path)?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "logs-compression")]
+    /// Compress the log file with zstd and remove the original one
+    pub fn compress(&self) -> anyhow::Result<Self> {
+        if self.compressed {
+            return Ok(self.clone());
+        }
+
+        let mut path = self.path.clone().into_os_string();
+
+        path.push(".");
+        path.push(COMPRESSED_EXTENSION);
+
+        let path = PathBuf::from(path);
+
+        zstd::stream::copy_encode(File::open(&self.path)?, File::create(&path)?, 0)?;
+
+        std::fs::remove_file(&self.path)?;
+
+        Ok(Self {
+            path,
+            started_at: self.started_at,
+            compressed: true
+        })
+    }
+}
+
+#[cfg(feature = "logs-compression")]
+fn decompress(file: File) -> anyhow::Result<Box<dyn Read + Send>> {
+    Ok(Box::new(zstd::stream::read::Decoder::new(file)?))
+}
+
+#[cfg(not(feature = "logs-compression"))]
+fn decompress(_file: File) -> anyhow::Result<Box<dyn Read + Send>> {
+    anyhow::bail!("Logs compression feature is disabled")
+}
+
+#[derive(Debug)]
+/// Reader of the new lines written to the log file
+pub struct LogFollower {
+    reader: BufReader<File>,
+    line: String
+}
+
+impl LogFollower {
+    /// Skip already written part of the log
+    pub fn skip_to_end(&mut self) -> anyhow::Result<()> {
+        self.reader.seek(SeekFrom::End(0))?;
+        self.line.clear();
+
+        Ok(())
+    }
+
+    /// Read complete lines written since the last call
+    pub fn read_lines(&mut self) -> anyhow::Result<Vec<String>> {
+        let mut lines = Vec::new();
+
+        loop {
+            let read = self.reader.read_line(&mut self.line)?;
+
+            // Keep partially written line until it's finished
+            if read == 0 || !self.line.ends_with('\n') {
+                break;
+            }
+
+            self.line.pop();
+
+            lines.push(std::mem::take(&mut self.line));
+        }
+
+        Ok(lines)
+    }
+
+    /// Wait for the new lines with given polling interval
+    ///
+    /// Empty list is returned if no lines were written until the timeout,
+    /// so the caller can check if the game is still running
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    ///
+    /// use anime_launcher_sdk::launch::logs::SessionLogs;
+    ///
+    /// let folder = std::env::temp_dir().join(format!("anime-launcher-sdk-logs-follow-{}", std::process::id()));
+    ///
+    /// let _ = std::fs::remove_dir_all(&folder);
+    ///
+    /// let log = SessionLogs::new(&folder).create().unwrap();
+    /// let mut follower = log.follow().unwrap();
+    ///
+    /// let start = Instant::now();
+    ///
+    /// assert!(follower.wait_lines(Duration::from_millis(50), Duration::from_millis(200)).unwrap().is_empty());
+    /// assert!(start.elapsed() >= Duration::from_millis(200));
+    ///
+    /// std::fs::write(&log.path, "line\n").unwrap();
+    ///
+    /// assert_eq!(follower.wait_lines(Duration::from_millis(50), Duration::from_secs(1)).unwrap(), ["line"]);
+    /// ```
+    pub fn wait_lines(&mut self, interval: Duration, timeout: Duration) -> anyhow::Result<Vec<String>> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let lines = self.read_lines()?;
+
+            if !lines.is_empty() {
+                return Ok(lines);
+            }
+
+            let now = Instant::now();
+
+            if now >= deadline {
+                return Ok(lines);
+            }
+
+            std::thread::sleep(interval.min(deadline - now));
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Folder with the game sessions logs
+///
+/// ```
+/// use anime_launcher_sdk::launch::logs::SessionLogs;
+///
+/// let folder = std::env::temp_dir().join(format!("anime-launcher-sdk-logs-test-{}", std::process::id()));
+///
+/// let _ = std::fs::remove_dir_all(&folder);
+///
+/// let logs = SessionLogs::new(&folder).with_retention(2);
+///
+/// for _ in 0..3 {
+///     logs.create().unwrap();
+/// }
+///
+/// assert_eq!(logs.list().unwrap().len(), 2);
+/// ```
+pub struct SessionLogs {
+    pub folder: PathBuf,
+
+    /// Amount of stored session logs. Older logs are deleted
+    pub retention: usize,
+
+    /// Compress logs of the previous sessions
+    pub compress: bool
+}
+
+impl SessionLogs {
+    #[inline]
+    pub fn new(folder: impl Into<PathBuf>) -> Self {
+        Self {
+            folder: folder.into(),
+            retention: 10,
+            compress: false
+        }
+    }
+
+    #[inline]
+    pub fn with_retention(self, retention: usize) -> Self {
+        Self {
+            retention,
+            ..self
+        }
+    }
+
+    #[inline]
+    pub fn with_compression(self, compress: bool) -> Self {
+        Self {
+            compress,
+            ..self
+        }
+    }
+
+    /// List stored session logs, newest first
+    pub fn list(&self) -> anyhow::Result<Vec<SessionLog>> {
+        if !self.folder.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut logs = self.folder.read_dir()?
+            .flatten()
+            .filter_map(|entry| SessionLog::from_path(entry.path()))
+            .collect::<Vec<_>>();
+
+        // File names contain session start time so they're sorted too
+        logs.sort_by(|a, b| b.path.file_name().cmp(&a.path.file_name()));
+
+        Ok(logs)
+    }
+
+    #[inline]
+    /// Get log of the latest session
+    pub fn latest(&self) -> anyhow::Result<Option<SessionLog>> {
+        Ok(self.list()?.into_iter().next())
+    }
+
+    /// Create empty log file for the new session
+    ///
+    /// Logs of the older sessions are compressed if needed,
+    /// and the oldest ones are deleted according to the retention
+    ///
+    /// ```
+    /// use anime_launcher_sdk::launch::logs::SessionLogs;
+    ///
+    /// let folder = std::env::temp_dir().join(format!("anime-launcher-sdk-logs-order-{}", std::process::id()));
+    ///
+    /// let logs = SessionLogs::new(&folder).with_retention(10);
+    ///
+    /// // Most of these logs are created in the same millisecond
+    /// let mut created = (0..10)
+    ///     .map(|_| logs.create().unwrap().path)
+    ///     .collect::<Vec<_>>();
+    ///
+    /// created.reverse();
+    ///
+    /// let listed = logs.list().unwrap()
+    ///     .into_iter()
+    ///     .map(|log| log.path)
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(listed, created);
+    ///
+    /// std::fs::remove_dir_all(folder).unwrap();
+    /// ```
+    pub fn create(&self) -> anyhow::Result<SessionLog> {
+        std::fs::create_dir_all(&self.folder)?;
+
+        let name = format_time(SystemTime::now())
+            .replace(' ', "_")
+            .replace(':', "-");
+
+        let mut path = self.folder.join(format!("game-{name}.log"));
+        let mut i = 1;
+
+        // Sessions started in the same millisecond get a suffix
+        // which keeps them sorted after the first one
+        while path.exists() {
+            path = self.folder.join(format!("game-{name}_{i:03}.log"));
+
+            i += 1;
+        }
+
+        File::create(&path)?;
+
+        self.cleanup(&path)?;
+
+        SessionLog::from_path(path)
+            .ok_or_else(|| anyhow::anyhow!("Failed to create session log file"))
+    }
+
+    fn cleanup(&self, current: &Path) -> anyhow::Result<()> {
+        let logs = self.list()?;
+
+        for (i, log) in logs.into_iter().enumerate() {
+            if i >= self.retention.max(1) {
+                std::fs::remove_file(&log.path)?;
+            }
+
+            #[cfg(feature = "logs-compression")]
+            if i < self.retention && self.compress && log.path != current {
+                log.compress()?;
+            }
+        }
+
+        #[cfg(not(feature = "logs-compression"))]
+        let _ = current;
+
+        Ok(())
+    }
+}
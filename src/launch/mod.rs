@@ -0,0 +1,648 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::fs::File;
+
+#[cfg(feature = "sandbox")]
+use crate::config::schema_blanks::sandbox::{SandboxMount, SandboxPaths};
+
+use crate::config::schema_blanks::hooks::GameHooks;
+use crate::config::schema_blanks::wrappers::{CustomWrapper, WrapperPosition};
+
+use crate::shell;
+
+use hooks::{LaunchHook, HookRunner, HookStage};
+use keywords::Keywords;
+use services::Service;
+
+pub mod process;
+pub mod analyzer;
+pub mod hooks;
+pub mod keywords;
+pub mod logs;
+pub mod playtime;
+pub mod services;
+
+mod builder;
+mod env;
+mod handle;
+mod outcome;
+mod steps;
+
+pub use builder::*;
+pub use env::*;
+pub use handle::*;
+pub use outcome::*;
+pub use steps::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wrapper {
+    /// Wrapper binary followed by its arguments
+    pub command: Vec<String>,
+
+    /// Put `--` between the wrapper and the wrapped command
+    pub separator: bool
+}
+
+impl Wrapper {
+    #[inline]
+    pub fn new<T: ToString>(command: impl IntoIterator<Item = T>) -> Self {
+        Self {
+            command: command.into_iter().map(|arg| arg.to_string()).collect(),
+            separator: false
+        }
+    }
+
+    #[inline]
+    /// Put `--` between the wrapper and the wrapped command
+    pub fn with_separator(self) -> Self {
+        Self {
+            separator: true,
+            ..self
+        }
+    }
+}
+
+/// Structured description of the game launch
+///
+/// Every game builds this struct with `LaunchPlan::from_config`,
+/// and the final command is produced from it only when
+/// the game is actually started:
+///
+/// ```text
+/// <limits> <sandbox> -- bash -c "<template>"
+///
+/// %command%         = %bash_command% %windows_command% %launch_args%
+/// %bash_command%    = <background> & <wrappers> <runner>
+/// %windows_command% = <windows_command>
+/// %launch_args%     = <launch_args>
+/// ```
+///
+/// All the command parts are stored as separate unquoted arguments
+/// with host paths. Paths are translated to the sandbox ones and arguments
+/// are escaped only when the final command is built, so paths
+/// with spaces, quotes or `$` are passed to the game as is:
+///
+/// ```
+/// use anime_launcher_sdk::launch::LaunchPlan;
+///
+/// let game = "/games/\"An\" Anime Game's $HOME `id` 100%";
+///
+/// let mut plan = LaunchPlan::new("/");
+///
+/// plan.runner.push(String::from("printf"));
+/// plan.windows_command.push(String::from("%s\\n"));
+/// plan.windows_command.push(format!("{game}/game.exe"));
+/// plan.launch_args.push(String::from("-arg"));
+///
+/// plan.template = Some(String::from("%command% '%game%' \"%game%\" %game%"));
+/// plan.keywords.insert("game", game);
+///
+/// let output = plan.to_command().unwrap().output().unwrap();
+///
+/// assert_eq!(
+///     String::from_utf8_lossy(&output.stdout),
+///     format!("{game}/game.exe\n-arg\n{game}\n{game}\n{game}\n")
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LaunchPlan {
+    /// Commands started in background together with the game, e.g. FPS unlocker
+    pub background: Vec<Vec<String>>,
+
+    /// Wrappers chain, outermost first (`gamescope`, `gamemoderun`, ...)
+    pub wrappers: Vec<Wrapper>,
+
+    /// Command used to run windows executables, e.g. path to the wine binary
+    pub runner: Vec<String>,
+
+    /// Windows part of the command: virtual desktop and the game executable
+    pub windows_command: Vec<String>,
+
+    /// Arguments passed to the game executable
+    pub launch_args: Vec<String>,
+
+    /// User-given launch command template
+    ///
+    /// Available keywords:
+    /// - `%command%` - whole launch command
+    /// - `%bash_command%` - wrappers and the runner
+    /// - `%windows_command%` - windows part of the command
+    /// - `%launch_args%` - game launch arguments
+    ///
+    /// Keywords from the `keywords` field are available here as well
+    pub template: Option<String>,
+
+    /// Values of additional template keywords, e.g. `%prefix%`
+    ///
+    /// Unlike command parts these values are escaped according
+    /// to the place where they're used in the template
+    pub keywords: Keywords,
+
+    /// Wrapper which limits resources of the whole process tree,
+    /// e.g. `systemd-run --scope`. It's applied outside of the sandbox
+    pub limits: Option<Wrapper>,
+
+    /// Sandbox wrapper applied to the whole command
+    pub sandbox: Option<Wrapper>,
+
+    /// Mounts used by the sandbox wrapper
+    #[cfg(feature = "sandbox")]
+    pub mounts: Vec<SandboxMount>,
+
+    /// Host paths available in the sandbox
+    ///
+    /// Every part of the command and environment values store host paths,
+    /// and they're translated using this map when the final command is built
+    #[cfg(feature = "sandbox")]
+    pub paths: SandboxPaths,
+
+    /// Environment variables set for the launched process
+    pub env: EnvLayers,
+
+    /// Working directory of the launched process
+    pub current_dir: PathBuf,
+
+    /// Wineserver used to stop the game
+    pub wineserver: Option<Wineserver>,
+
+    /// Stop the game after the given time, e.g. because of the playtime limits
+    pub time_limit: Option<TimeLimit>,
+
+    /// Commands executed before the launch and after the game exit
+    ///
+    /// Hooks are executed outside of the sandbox
+    pub hooks: Vec<LaunchHook>,
+
+    /// Helper processes started outside of the sandbox before the game
+    /// and stopped after all the game processes have exited
+    pub services: Vec<Service>,
+
+    /// Files written right before the launch, e.g. the sandbox hosts file
+    pub files: HashMap<PathBuf, String>
+}
+
+impl LaunchPlan {
+    #[inline]
+    pub fn new(current_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            current_dir: current_dir.into(),
+            ..Self::default()
+        }
+    }
+
+    #[inline]
+    /// Set environment variable
+    ///
+    /// `source` is the name of the setting which sets this variable.
+    /// If `key` ends with `+` then the value is appended to the one
+    /// set by the previous settings, see `EnvLayers::add`
+    pub fn env(&mut self, source: impl ToString, key: impl ToString, value: impl ToString) {
+        self.env.add(source, key.to_string(), value);
+    }
+
+    /// Set list of environment variables
+    ///
+    /// `source` is the name of the setting which sets these variables
+    pub fn envs<K: ToString, V: ToString>(&mut self, source: impl ToString, vars: impl IntoIterator<Item = (K, V)>) {
+        let source = source.to_string();
+
+        for (key, value) in vars {
+            self.env(&source, key, value);
+        }
+    }
+
+    /// Append values to the path-like environment variables
+    ///
+    /// Values are joined with the ones set by the previous settings
+    /// instead of overriding them, see `env_separator`
+    pub fn append_envs<K: ToString, V: ToString>(&mut self, source: impl ToString, vars: impl IntoIterator<Item = (K, V)>) {
+        let source = source.to_string();
+
+        for (key, value) in vars {
+            self.env.append(&source, key, value);
+        }
+    }
+
+    #[cfg(feature = "sandbox")]
+    /// Translate host paths in the value to the sandbox ones
+    pub fn translate(&self, value: impl AsRef<str>) -> String {
+        self.paths.translate(value)
+    }
+
+    #[cfg(not(feature = "sandbox"))]
+    /// Translate host paths in the value to the sandbox ones
+    pub fn translate(&self, value: impl AsRef<str>) -> String {
+        value.as_ref().to_string()
+    }
+
+    fn translate_all<T: AsRef<str>>(&self, values: impl IntoIterator<Item = T>) -> Vec<String> {
+        values.into_iter()
+            .map(|value| self.translate(value))
+            .collect()
+    }
+
+    /// Get environment variables with translated paths
+    pub fn get_env(&self) -> HashMap<String, String> {
+        self.env.map_values(|value| self.translate(value)).resolve()
+    }
+
+    /// Add hooks from the game config
+    ///
+    /// Hooks are executed outside of the sandbox,
+    /// so their paths are not translated
+    pub fn add_hooks(&mut self, hooks: &GameHooks) -> anyhow::Result<()> {
+        let stages = [
+            (HookStage::PreLaunch, &hooks.pre_launch),
+            (HookStage::PostExit, &hooks.post_exit),
+            (HookStage::PlaytimeWarning, &hooks.playtime_warning)
+        ];
+
+        for (stage, hooks) in stages {
+            for hook in hooks {
+                self.hooks.push(LaunchHook::from_config(stage, hook, &self.keywords)?);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add enabled user wrappers placed at the given position
+    ///
+    /// Keywords are replaced in the wrappers arguments.
+    /// Their environment is set by `add_wrappers_env`
+    ///
+    /// ```
+    /// use anime_launcher_sdk::config::schema_blanks::wrappers::*;
+    /// use anime_launcher_sdk::launch::{LaunchPlan, Wrapper};
+    ///
+    /// let wrappers = [
+    ///     CustomWrapper {
+    ///         binary: String::from("prime-run"),
+    ///         position: WrapperPosition::Outer,
+    ///         ..CustomWrapper::default()
+    ///     },
+    ///     CustomWrapper {
+    ///         binary: String::from("strace"),
+    ///         args: vec![String::from("-o"), String::from("%game%/strace.log")],
+    ///         ..CustomWrapper::default()
+    ///     },
+    ///     CustomWrapper {
+    ///         binary: String::from("obs-gamecapture"),
+    ///         enabled: false,
+    ///         ..CustomWrapper::default()
+    ///     }
+    /// ];
+    ///
+    /// let mut plan = LaunchPlan::new("/");
+    ///
+    /// plan.keywords.insert("game", "/games/game");
+    ///
+    /// plan.add_wrappers(&wrappers, WrapperPosition::Outer).unwrap();
+    /// plan.wrappers.push(Wrapper::new(["gamemoderun"]));
+    /// plan.add_wrappers(&wrappers, WrapperPosition::Inner).unwrap();
+    ///
+    /// plan.runner.push(String::from("wine"));
+    ///
+    /// assert_eq!(plan.get_bash_command(), "prime-run gamemoderun strace -o /games/game/strace.log wine");
+    /// ```
+    pub fn add_wrappers(&mut self, wrappers: &[CustomWrapper], position: WrapperPosition) -> anyhow::Result<()> {
+        for wrapper in wrappers {
+            if wrapper.enabled && wrapper.position == position {
+                let command = wrapper.get_command()
+                    .into_iter()
+                    .map(|arg| self.keywords.replace(arg))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+
+                self.wrappers.push(Wrapper::new(command));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Set environment variables of the enabled user wrappers
+    pub fn add_wrappers_env(&mut self, wrappers: &[CustomWrapper]) {
+        for wrapper in wrappers.iter().filter(|wrapper| wrapper.enabled) {
+            let source = format!("{} wrapper", wrapper.binary);
+
+            for (key, value) in &wrapper.env {
+                self.env(&source, key, self.keywords.replace_env(value));
+            }
+        }
+    }
+
+    /// Get `%bash_command%` part of the command
+    pub fn get_bash_command(&self) -> String {
+        let mut command = Vec::new();
+
+        for background in &self.background {
+            command.push(shell::join(self.translate_all(background)));
+            command.push(String::from("&"));
+        }
+
+        for wrapper in &self.wrappers {
+            command.push(shell::join(self.translate_all(&wrapper.command)));
+
+            if wrapper.separator {
+                command.push(String::from("--"));
+            }
+        }
+
+        command.push(shell::join(self.translate_all(&self.runner)));
+
+        command.join(" ")
+    }
+
+    #[inline]
+    /// Get `%windows_command%` part of the command
+    pub fn get_windows_command(&self) -> String {
+        shell::join(self.translate_all(&self.windows_command))
+    }
+
+    #[inline]
+    /// Get `%launch_args%` part of the command
+    pub fn get_launch_args(&self) -> String {
+        shell::join(self.translate_all(&self.launch_args))
+    }
+
+    /// Get the launch command without the sandbox wrapper
+    ///
+    /// User-given template is applied here if it's set.
+    /// Unknown keywords in the template are kept as is
+    pub fn get_command(&self) -> anyhow::Result<String> {
+        let bash_command = self.get_bash_command();
+        let windows_command = self.get_windows_command();
+        let launch_args = self.get_launch_args();
+
+        let command = [&bash_command, &windows_command, &launch_args]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        match &self.template {
+            Some(template) => {
+                let keywords = self.keywords.iter()
+                    .fold(Keywords::new(), |keywords, (name, value)| keywords.with(name, self.translate(value)));
+
+                keywords.substitute(self.translate(template), &[
+                    ("command", &command),
+                    ("bash_command", &bash_command),
+                    ("windows_command", &windows_command),
+                    ("launch_args", &launch_args)
+                ])
+            }
+
+            None => Ok(command)
+        }
+    }
+
+    /// Get final list of arguments used to run the game
+    ///
+    /// The command is passed to `bash -c` which is started
+    /// inside of the sandbox if it's enabled
+    pub fn get_argv(&self) -> anyhow::Result<Vec<String>> {
+        let mut argv = Vec::new();
+
+        for wrapper in [&self.limits, &self.sandbox].into_iter().flatten() {
+            argv.extend(wrapper.command.iter().cloned());
+
+            if wrapper.separator {
+                argv.push(String::from("--"));
+            }
+        }
+
+        argv.extend([
+            String::from("bash"),
+            String::from("-c"),
+            self.get_command()?
+        ]);
+
+        Ok(argv)
+    }
+
+    /// Resolve current plan without running anything
+    pub fn resolve(&self) -> anyhow::Result<ResolvedLaunch> {
+        Ok(ResolvedLaunch {
+            argv: self.get_argv()?,
+            env: self.env.map_values(|value| self.translate(value)).report(),
+            current_dir: self.current_dir.clone(),
+
+            #[cfg(feature = "sandbox")]
+            mounts: self.mounts.clone()
+        })
+    }
+
+    /// Build `std::process::Command` from the current plan
+    pub fn to_command(&self) -> anyhow::Result<Command> {
+        let argv = self.get_argv()?;
+
+        let mut command = Command::new(&argv[0]);
+
+        command.args(&argv[1..]);
+
+        command.envs(self.get_env());
+
+        command.current_dir(&self.current_dir);
+
+        Ok(command)
+    }
+
+    /// Start the game and redirect its output to the `log_file`
+    ///
+    /// Blocking pre-launch hooks are executed before that, and post-exit
+    /// hooks are executed after `on_exit`, or right away if the game
+    /// has failed to start. Their failures are reported in the session outcome
+    ///
+    /// The command is started in its own process group, and the game
+    /// is considered closed only when every process of this group
+    /// and every descendant of the started command has exited.
+    /// `on_exit` is called after that in the background thread
+    /// and can add suggestions to the outcome returned by `GameHandle::wait`
+    ///
+    /// ```
+    /// use anime_launcher_sdk::launch::*;
+    ///
+    /// let mut plan = LaunchPlan::new("/");
+    ///
+    /// plan.runner.push(String::from("echo"));
+    /// plan.launch_args.push(String::from("Hello, World!"));
+    ///
+    /// let log_file = std::env::temp_dir().join("anime-launcher-sdk-spawn-test.log");
+    ///
+    /// let handle = plan.spawn(&log_file, 1024, |outcome| {
+    ///     outcome.suggestions.push(Suggestion::EnableTimeoutFix);
+    ///
+    ///     Ok(())
+    /// }).unwrap();
+    ///
+    /// while handle.status() != GameStatus::Exited {
+    ///     std::thread::sleep(std::time::Duration::from_millis(100));
+    /// }
+    ///
+    /// let lines = handle.lines().collect::<Vec<_>>();
+    ///
+    /// assert_eq!(lines[0].stream, LogStream::Stdout);
+    /// assert_eq!(lines[0].line, "Hello, World!");
+    ///
+    /// let outcome = handle.wait().unwrap();
+    ///
+    /// assert!(outcome.success());
+    /// assert_eq!(outcome.tree_end, ProcessTreeEnd::Exited);
+    /// assert!(!outcome.log_limit_reached);
+    /// assert!(outcome.has_suggestion(Suggestion::EnableTimeoutFix));
+    ///
+    /// assert!(std::fs::read_to_string(&log_file).unwrap().ends_with("] [stdout] Hello, World!\n"));
+    ///
+    /// // Post-exit hooks are executed if the game has failed to start
+    /// let marker = std::env::temp_dir().join(format!("anime-launcher-sdk-spawn-marker-{}", std::process::id()));
+    ///
+    /// plan.limits = Some(Wrapper::new(["/an-anime-binary"]));
+    ///
+    /// plan.hooks.push(hooks::LaunchHook {
+    ///     stage: hooks::HookStage::PostExit,
+    ///     command: format!("touch '{}'", marker.display()),
+    ///     blocking: true,
+    ///     timeout: None
+    /// });
+    ///
+    /// assert!(plan.spawn(&log_file, 1024, |_| Ok(())).is_err());
+    /// assert!(marker.exists());
+    ///
+    /// std::fs::remove_file(marker).unwrap();
+    /// ```
+    #[tracing::instrument(level = "debug", skip(self, on_exit))]
+    pub fn spawn(
+        &self,
+        log_file: impl AsRef<Path> + std::fmt::Debug,
+        log_limit: usize,
+        on_exit: impl FnOnce(&mut RunOutcome) -> anyhow::Result<()> + Send + 'static
+    ) -> anyhow::Result<GameHandle> {
+        use std::os::unix::process::CommandExt;
+
+        let variables = self.get_env().iter()
+            .map(|(key, value)| format!("{key}={}", shell::quote(value)))
+            .fold(String::new(), |acc, env| acc + " " + &env);
+
+        tracing::info!("Running the game with command:{variables} {}", shell::join(self.get_argv()?));
+
+        // Command is built before any side effects
+        // so its errors don't leave services or hooks behind
+        let mut command = self.to_command()?;
+
+        command.process_group(0)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        // Create new log file to log all the game output
+        let log_file = File::create(log_file.as_ref())?;
+
+        for (path, content) in &self.files {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+
+            std::fs::write(path, content)?;
+        }
+
+        // Services are started before the hooks since the environment
+        // given to them can already point to the services, e.g. D-Bus proxy socket
+        let services = services::start_services(&self.services)?;
+
+        // Hooks are executed outside of the sandbox
+        // so they get environment with the host paths
+        let hooks = HookRunner::new(self.hooks.clone(), self.env.resolve(), &self.current_dir);
+
+        hooks.run(HookStage::PreLaunch);
+
+        let child = match command.spawn() {
+            Ok(child) => child,
+
+            // Undo what the pre-launch hooks have done
+            Err(err) => {
+                services::stop_services(services);
+
+                hooks.run(HookStage::PostExit);
+
+                return Err(err.into());
+            }
+        };
+
+        let mut handle = GameHandle::new(child, log_file, log_limit, self.wineserver.clone(), services, hooks.clone(), on_exit);
+
+        if let Some(limit) = self.time_limit {
+            handle.watch_time_limit(limit, hooks);
+        }
+
+        Ok(handle)
+    }
+
+    #[inline]
+    /// Run the game, redirect its output to the `log_file` and freeze
+    /// the current thread until the game is closed
+    ///
+    /// See `spawn`
+    pub fn run(&self, log_file: impl AsRef<Path> + std::fmt::Debug, log_limit: usize) -> anyhow::Result<RunOutcome> {
+        self.spawn(log_file, log_limit, |_| Ok(()))?.wait()
+    }
+}
+
+/// Exact command, environment and working directory
+/// which will be used to run the game
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedLaunch {
+    pub argv: Vec<String>,
+
+    /// Environment variables sorted by their names
+    ///
+    /// Every variable contains the list of settings which
+    /// have set or overridden its value
+    pub env: Vec<ResolvedEnv>,
+
+    pub current_dir: PathBuf,
+
+    /// Mounts used by the sandbox. Empty if sandbox is disabled
+    #[cfg(feature = "sandbox")]
+    pub mounts: Vec<SandboxMount>
+}
+
+impl ResolvedLaunch {
+    /// Get standalone shell script which runs the game
+    /// the same way as the launcher does
+    pub fn to_script(&self) -> String {
+        let mut script = String::from("#!/usr/bin/env bash\n\n");
+
+        script += &format!("cd {}\n\n", shell::quote(self.current_dir.to_string_lossy()));
+
+        for var in &self.env {
+            // Skip the first line with the variable value
+            for line in var.explain().lines().skip(1) {
+                script += &format!("#{line}\n");
+            }
+
+            script += &format!("export {}={}\n\n", var.name, shell::quote(&var.value));
+        }
+
+        let argv = self.argv.iter()
+            .map(shell::quote)
+            .collect::<Vec<_>>();
+
+        script += &format!("exec {}\n", argv.join(" "));
+
+        script
+    }
+
+    /// Write standalone shell script to the given path
+    ///
+    /// See `to_script`
+    pub fn write_script(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        std::fs::write(path.as_ref(), self.to_script())?;
+        std::fs::set_permissions(path.as_ref(), std::fs::Permissions::from_mode(0o755))?;
+
+        Ok(())
+    }
+}
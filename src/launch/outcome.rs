@@ -0,0 +1,90 @@
+use std::process::ExitStatus;
+use std::time::Duration;
+
+use serde::{Serialize, Deserialize};
+
+use super::hooks::HookFailure;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// How the game processes have ended
+pub enum ProcessTreeEnd {
+    /// Spawned command was the last game process to exit
+    Exited,
+
+    /// Spawned command has exited before some of the processes
+    /// it started, e.g. wine has left the game running in background
+    Detached,
+
+    /// Game was stopped using `GameHandle::kill`
+    /// or because of the time limit
+    Killed
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Typed advice shown to the user after the game was closed
+pub enum Suggestion {
+    /// Game has crashed shortly after the start and created
+    /// `driverError.log` file, which usually means that
+    /// `WINE_ENABLE_TIMEOUT_FIX` should be enabled
+    EnableTimeoutFix
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Result of the game session
+pub struct RunOutcome {
+    /// Exit code of the spawned command. `None` if it was killed by a signal
+    pub exit_code: Option<i32>,
+
+    /// Signal which has killed the spawned command
+    pub signal: Option<i32>,
+
+    /// Time passed since the game start until all its processes have exited
+    pub duration: Duration,
+
+    /// How the game processes have ended
+    pub tree_end: ProcessTreeEnd,
+
+    /// Game log file has reached its size limit, so the rest
+    /// of the game output wasn't saved
+    pub log_limit_reached: bool,
+
+    /// Game was stopped because of the playtime limits
+    pub time_limit_reached: bool,
+
+    /// List of suggestions for the user
+    pub suggestions: Vec<Suggestion>,
+
+    /// Pre-launch and post-exit hooks which have failed
+    ///
+    /// Failures of the background post-exit hooks
+    /// are only written to the launcher log
+    pub hook_failures: Vec<HookFailure>
+}
+
+impl RunOutcome {
+    pub(crate) fn new(status: ExitStatus, duration: Duration, tree_end: ProcessTreeEnd, log_limit_reached: bool) -> Self {
+        use std::os::unix::process::ExitStatusExt;
+
+        Self {
+            exit_code: status.code(),
+            signal: status.signal(),
+            duration,
+            tree_end,
+            log_limit_reached,
+            time_limit_reached: false,
+            suggestions: Vec::new(),
+            hook_failures: Vec::new()
+        }
+    }
+
+    #[inline]
+    /// Check if the spawned command has exited with zero code
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+
+    #[inline]
+    pub fn has_suggestion(&self, suggestion: Suggestion) -> bool {
+        self.suggestions.contains(&suggestion)
+    }
+}
@@ -0,0 +1,718 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Serialize, Deserialize};
+
+use crate::config::schema_blanks::playtime::PlaytimeLimits;
+
+use super::keywords::Keywords;
+use super::logs::format_time;
+use super::{RunOutcome, ProcessTreeEnd, TimeLimit};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Single game session saved in the playtime history
+pub struct PlaytimeRecord {
+    /// Unix timestamp of the session start in seconds
+    pub started_at: u64,
+
+    /// Unix timestamp of the session end in seconds
+    pub ended_at: u64,
+
+    /// Time passed since the game start until all its processes have exited, in seconds
+    pub duration: u64,
+
+    /// Game edition, e.g. `global`
+    pub edition: String,
+
+    pub game_version: Option<String>,
+    pub wine: Option<String>,
+    pub dxvk: Option<String>,
+
+    /// Name of the account session used to run the game
+    pub account: Option<String>,
+
+    pub exit_code: Option<i32>,
+    pub signal: Option<i32>,
+    pub tree_end: ProcessTreeEnd
+}
+
+impl PlaytimeRecord {
+    /// Start record of the session from the game launch keywords
+    ///
+    /// Session start time is set to the current time
+    ///
+    /// ```
+    /// use anime_launcher_sdk::launch::keywords::Keywords;
+    /// use anime_launcher_sdk::launch::playtime::PlaytimeRecord;
+    ///
+    /// let keywords = Keywords::new()
+    ///     .with("edition", "global")
+    ///     .with("game_version", "5.1.0")
+    ///     .with("wine_name", "wine-9.22")
+    ///     .with("dxvk_version", "");
+    ///
+    /// let record = PlaytimeRecord::from_keywords(&keywords);
+    ///
+    /// assert_eq!(record.edition, "global");
+    /// assert_eq!(record.game_version.as_deref(), Some("5.1.0"));
+    /// assert_eq!(record.wine.as_deref(), Some("wine-9.22"));
+    /// assert_eq!(record.dxvk, None);
+    /// ```
+    pub fn from_keywords(keywords: &Keywords) -> Self {
+        let get = |name: &str| keywords.get(name)
+            .filter(|value| !value.is_empty())
+            .map(String::from);
+
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        Self {
+            started_at,
+            ended_at: started_at,
+            duration: 0,
+            edition: get("edition").unwrap_or_default(),
+            game_version: get("game_version"),
+            wine: get("wine_name"),
+            dxvk: get("dxvk_version"),
+            account: None,
+            exit_code: None,
+            signal: None,
+            tree_end: ProcessTreeEnd::Exited
+        }
+    }
+
+    /// Finish the record with the session outcome
+    ///
+    /// Session end time is set to the current time
+    pub fn finish(&mut self, outcome: &RunOutcome) {
+        self.touch();
+
+        self.duration = outcome.duration.as_secs();
+        self.exit_code = outcome.exit_code;
+        self.signal = outcome.signal;
+        self.tree_end = outcome.tree_end;
+    }
+
+    /// Set session end time to the current time
+    /// as if the game was closed right now
+    pub fn touch(&mut self) {
+        self.ended_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+            .max(self.started_at);
+
+        self.duration = self.ended_at - self.started_at;
+    }
+
+    #[inline]
+    pub fn start_time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.started_at)
+    }
+
+    #[inline]
+    pub fn end_time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.ended_at)
+    }
+
+    #[inline]
+    pub fn playtime(&self) -> Duration {
+        Duration::from_secs(self.duration)
+    }
+
+    #[inline]
+    /// Check if the game has exited with zero code
+    /// and wasn't killed by the launcher
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0) && self.tree_end != ProcessTreeEnd::Killed
+    }
+}
+
+/// Source of the current time used by the playtime limits
+pub trait Clock {
+    /// Get current time
+    fn now(&self) -> SystemTime;
+
+    /// Get offset of the local time from UTC in seconds
+    ///
+    /// Called once per playtime allowance check
+    fn utc_offset(&self) -> i64;
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Clock of the current system
+///
+/// ```
+/// use anime_launcher_sdk::launch::playtime::{Clock, SystemClock};
+///
+/// assert!(SystemClock.utc_offset().abs() <= 14 * 3600);
+/// ```
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[inline]
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    /// Get local time offset from the system time zone
+    ///
+    /// UTC is used if it can't be detected
+    fn utc_offset(&self) -> i64 {
+        let now = self.now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as libc::time_t;
+
+        let mut time = unsafe { std::mem::zeroed::<libc::tm>() };
+
+        if unsafe { libc::localtime_r(&now, &mut time) }.is_null() {
+            tracing::warn!("Failed to get local time offset. Using UTC");
+
+            return 0;
+        }
+
+        time.tm_gmtoff as i64
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Clock which always returns the same time
+pub struct FixedClock {
+    pub time: SystemTime,
+
+    /// Offset of the local time from UTC in seconds
+    pub utc_offset: i64
+}
+
+impl Clock for FixedClock {
+    #[inline]
+    fn now(&self) -> SystemTime {
+        self.time
+    }
+
+    #[inline]
+    fn utc_offset(&self) -> i64 {
+        self.utc_offset
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Time the game can be played now according to the playtime limits
+pub enum PlaytimeAllowance {
+    /// Game has no playtime limits
+    Unlimited,
+
+    /// Game can be played for the given time
+    Remaining(Duration),
+
+    /// Daily playtime budget is spent
+    BudgetSpent,
+
+    /// Current time is outside of the allowed time windows
+    OutsideWindows
+}
+
+impl PlaytimeAllowance {
+    #[inline]
+    pub fn is_allowed(&self) -> bool {
+        matches!(self, Self::Unlimited | Self::Remaining(_))
+    }
+
+    /// Get time limit of the game session
+    ///
+    /// Fails if the game can't be played now
+    pub fn get_time_limit(&self, warning: Duration) -> anyhow::Result<Option<TimeLimit>> {
+        match self {
+            Self::Unlimited => Ok(None),
+
+            Self::Remaining(playtime) => Ok(Some(TimeLimit {
+                playtime: *playtime,
+                warning
+            })),
+
+            Self::BudgetSpent => anyhow::bail!("Daily playtime budget is spent"),
+            Self::OutsideWindows => anyhow::bail!("Game can't be played at this time")
+        }
+    }
+}
+
+/// Get `YYYY-MM-DD` date of the given unix timestamp in UTC
+fn format_day(secs: u64) -> String {
+    format_time(UNIX_EPOCH + Duration::from_secs(secs))[..10].to_string()
+}
+
+/// Get number of the local day since 1970-01-01 of the given unix timestamp
+fn local_day(secs: u64, utc_offset: i64) -> u64 {
+    (secs as i64 + utc_offset).div_euclid(86400).max(0) as u64
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// History of the game sessions stored in the JSON lines file
+///
+/// Playtime of a session is counted for the local day (week) it was started at.
+/// Weeks start on monday
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use anime_launcher_sdk::launch::playtime::*;
+/// use anime_launcher_sdk::launch::ProcessTreeEnd;
+///
+/// let path = std::env::temp_dir().join(format!("playtime-{}.jsonl", std::process::id()));
+///
+/// let record = |started_at: u64, duration: u64, account: Option<&str>, wine: &str, exit_code: i32| PlaytimeRecord {
+///     started_at,
+///     ended_at: started_at + duration,
+///     duration,
+///     edition: String::from("global"),
+///     game_version: Some(String::from("5.1.0")),
+///     wine: Some(String::from(wine)),
+///     dxvk: None,
+///     account: account.map(String::from),
+///     exit_code: Some(exit_code),
+///     signal: None,
+///     tree_end: ProcessTreeEnd::Exited
+/// };
+///
+/// // 2025-10-13 (monday), 2025-10-17 (friday) and 2025-10-20 (monday) 12:00 UTC
+/// PlaytimeHistory::append(&path, &record(1_760_356_800, 3600, Some("main"), "wine-9.20", 0)).unwrap();
+/// PlaytimeHistory::append(&path, &record(1_760_702_400, 1800, Some("alt"), "wine-9.21", 1)).unwrap();
+/// PlaytimeHistory::append(&path, &record(1_760_961_600, 600, Some("main"), "wine-9.22", 0)).unwrap();
+///
+/// let history = PlaytimeHistory::load(&path).unwrap();
+///
+/// assert_eq!(history.total(), Duration::from_secs(6000));
+///
+/// assert_eq!(history.per_day(0)["2025-10-17"], Duration::from_secs(1800));
+/// assert_eq!(history.per_week(0)["2025-10-13"], Duration::from_secs(5400));
+/// assert_eq!(history.per_week(0)["2025-10-20"], Duration::from_secs(600));
+///
+/// // Mondays 12:00 UTC are still sundays of the previous weeks in UTC-13
+/// assert_eq!(history.per_day(-13 * 3600)["2025-10-19"], Duration::from_secs(600));
+/// assert_eq!(history.per_week(-13 * 3600)["2025-10-06"], Duration::from_secs(3600));
+/// assert_eq!(history.per_week(-13 * 3600)["2025-10-13"], Duration::from_secs(2400));
+///
+/// // And 2025-10-17 12:00 UTC is already saturday 2025-10-18 in UTC+14
+/// assert_eq!(history.per_day(14 * 3600)["2025-10-18"], Duration::from_secs(1800));
+///
+/// assert_eq!(history.per_account()[&Some(String::from("main"))], Duration::from_secs(4200));
+///
+/// assert_eq!(history.last_successful().unwrap().wine.as_deref(), Some("wine-9.22"));
+/// assert!(history.edition("china").records.is_empty());
+///
+/// std::fs::remove_file(path).unwrap();
+/// ```
+pub struct PlaytimeHistory {
+    /// Saved sessions in the order they were finished
+    ///
+    /// Unfinished session is the last one, see `PlaytimeSession`
+    pub records: Vec<PlaytimeRecord>
+}
+
+impl PlaytimeHistory {
+    /// Read history from the given file
+    ///
+    /// Unfinished session of this history is counted up to
+    /// its latest update. Returns empty history if there's none.
+    /// Broken lines are skipped
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+
+        let mut history = Self::default();
+
+        if path.exists() {
+            history.records = Self::read_records(path)?;
+        }
+
+        if let Some(record) = PlaytimeSession::read_unfinished(path)? {
+            history.records.push(record);
+        }
+
+        Ok(history)
+    }
+
+    fn read_records(path: &Path) -> anyhow::Result<Vec<PlaytimeRecord>> {
+        let records = std::fs::read_to_string(path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(record) => Some(record),
+
+                Err(err) => {
+                    tracing::warn!("Skipping broken playtime record: {err}");
+
+                    None
+                }
+            })
+            .collect();
+
+        Ok(records)
+    }
+
+    /// Add record to the end of the given history file
+    pub fn append(path: impl AsRef<Path>, record: &PlaytimeRecord) -> anyhow::Result<()> {
+        let path = path.as_ref();
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+
+        writeln!(file, "{}", serde_json::to_string(record)?)?;
+
+        Ok(())
+    }
+
+    /// Get history of the given game edition
+    pub fn edition(&self, edition: impl AsRef<str>) -> Self {
+        Self {
+            records: self.records.iter()
+                .filter(|record| record.edition == edition.as_ref())
+                .cloned()
+                .collect()
+        }
+    }
+
+    /// Get total playtime
+    pub fn total(&self) -> Duration {
+        self.records.iter()
+            .map(PlaytimeRecord::playtime)
+            .sum()
+    }
+
+    /// Get playtime grouped by `YYYY-MM-DD` local days
+    ///
+    /// `utc_offset` is the offset of the local time from UTC
+    /// in seconds, e.g. the one returned by `Clock::utc_offset`
+    pub fn per_day(&self, utc_offset: i64) -> BTreeMap<String, Duration> {
+        self.group_by(|record| format_day(local_day(record.started_at, utc_offset) * 86400))
+    }
+
+    /// Get playtime grouped by local weeks. Keys are `YYYY-MM-DD` days of week mondays
+    ///
+    /// See `per_day`
+    pub fn per_week(&self, utc_offset: i64) -> BTreeMap<String, Duration> {
+        self.group_by(|record| {
+            let day = local_day(record.started_at, utc_offset);
+
+            // 1970-01-01 was thursday
+            let monday = day.saturating_sub((day + 3) % 7);
+
+            format_day(monday * 86400)
+        })
+    }
+
+    /// Get playtime grouped by account sessions. `None` is used
+    /// when the game was run without any session selected
+    pub fn per_account(&self) -> HashMap<Option<String>, Duration> {
+        let mut totals = HashMap::new();
+
+        for record in &self.records {
+            *totals.entry(record.account.clone()).or_default() += record.playtime();
+        }
+
+        totals
+    }
+
+    /// Get playtime of the sessions between the given unix timestamps
+    ///
+    /// Only overlapping parts of the sessions are counted. If `account`
+    /// is given then only sessions of this account are counted
+    pub fn played_between(&self, from: u64, to: u64, account: Option<&str>) -> Duration {
+        let secs = self.records.iter()
+            .filter(|record| account.is_none() || record.account.as_deref() == account)
+            .map(|record| {
+                // Session start time includes time needed to prepare the launch
+                let start = record.ended_at.saturating_sub(record.duration);
+
+                record.ended_at.min(to).saturating_sub(start.max(from))
+            })
+            .sum();
+
+        Duration::from_secs(secs)
+    }
+
+    /// Get time the game can be played now by the given account
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// use anime_launcher_sdk::config::schema_blanks::playtime::PlaytimeLimits;
+    /// use anime_launcher_sdk::launch::playtime::*;
+    /// use anime_launcher_sdk::launch::ProcessTreeEnd;
+    ///
+    /// let limits = PlaytimeLimits::from(&serde_json::json!({
+    ///     "enabled": true,
+    ///     "daily_minutes": 120,
+    ///     "accounts": {
+    ///         "kid": {
+    ///             "daily_minutes": 60,
+    ///             "windows": ["16:00-20:00"]
+    ///         }
+    ///     }
+    /// }));
+    ///
+    /// // 2025-10-17 15:00 in UTC, 18:00 in UTC+3
+    /// let now = 1_760_713_200;
+    ///
+    /// let clock = FixedClock {
+    ///     time: UNIX_EPOCH + Duration::from_secs(now),
+    ///     utc_offset: 3 * 3600
+    /// };
+    ///
+    /// let session = |ended_at: u64, duration: u64, account: &str| PlaytimeRecord {
+    ///     started_at: ended_at - duration,
+    ///     ended_at,
+    ///     duration,
+    ///     edition: String::from("global"),
+    ///     game_version: None,
+    ///     wine: None,
+    ///     dxvk: None,
+    ///     account: Some(String::from(account)),
+    ///     exit_code: Some(0),
+    ///     signal: None,
+    ///     tree_end: ProcessTreeEnd::Exited
+    /// };
+    ///
+    /// let history = PlaytimeHistory {
+    ///     records: vec![
+    ///         // Session of the previous local day is not counted
+    ///         session(now - 20 * 3600, 3600, "kid"),
+    ///         session(now - 3600, 1200, "kid"),
+    ///         session(now - 600, 3000, "parent")
+    ///     ]
+    /// };
+    ///
+    /// // 40 minutes are left from the daily budget of the account
+    /// assert_eq!(history.get_allowance(&limits, Some("kid"), &clock).unwrap(), PlaytimeAllowance::Remaining(Duration::from_secs(2400)));
+    ///
+    /// // Game budget counts sessions of all the accounts
+    /// assert_eq!(history.get_allowance(&limits, Some("parent"), &clock).unwrap(), PlaytimeAllowance::Remaining(Duration::from_secs(3000)));
+    ///
+    /// // 21:00 in UTC+3 is outside of the allowed window
+    /// let clock = FixedClock {
+    ///     time: clock.time + Duration::from_secs(3 * 3600),
+    ///     ..clock
+    /// };
+    ///
+    /// assert_eq!(history.get_allowance(&limits, Some("kid"), &clock).unwrap(), PlaytimeAllowance::OutsideWindows);
+    /// assert_eq!(history.get_allowance(&PlaytimeLimits::default(), Some("kid"), &clock).unwrap(), PlaytimeAllowance::Unlimited);
+    /// ```
+    pub fn get_allowance(&self, limits: &PlaytimeLimits, account: Option<&str>, clock: &impl Clock) -> anyhow::Result<PlaytimeAllowance> {
+        if !limits.enabled {
+            return Ok(PlaytimeAllowance::Unlimited);
+        }
+
+        let budget = limits.get_budget(account);
+
+        let now = clock.now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        // Second of the local day
+        let second = (now as i64 + clock.utc_offset()).rem_euclid(86400) as u64;
+
+        let mut remaining = None;
+
+        let windows = budget.get_windows()?;
+
+        if !windows.is_empty() {
+            let Some(left) = windows.iter().filter_map(|window| window.get_seconds_left(second)).max()
+            else {
+                return Ok(PlaytimeAllowance::OutsideWindows);
+            };
+
+            remaining = Some(left);
+        }
+
+        if let Some(minutes) = budget.daily_minutes {
+            let account = account.filter(|_| limits.has_account_budget(account));
+
+            let played = self.played_between(now.saturating_sub(second), now, account).as_secs();
+
+            let left = (minutes * 60).saturating_sub(played);
+
+            if left == 0 {
+                return Ok(PlaytimeAllowance::BudgetSpent);
+            }
+
+            remaining = Some(remaining.map_or(left, |remaining: u64| remaining.min(left)));
+        }
+
+        Ok(match remaining {
+            Some(left) => PlaytimeAllowance::Remaining(Duration::from_secs(left)),
+            None => PlaytimeAllowance::Unlimited
+        })
+    }
+
+    /// Get the latest session which has finished successfully
+    pub fn last_successful(&self) -> Option<&PlaytimeRecord> {
+        self.records.iter()
+            .rev()
+            .find(|record| record.success())
+    }
+
+    fn group_by(&self, key: impl Fn(&PlaytimeRecord) -> String) -> BTreeMap<String, Duration> {
+        let mut totals = BTreeMap::new();
+
+        for record in &self.records {
+            *totals.entry(key(record)).or_default() += record.playtime();
+        }
+
+        totals
+    }
+}
+
+/// Game session which is counted by the playtime history while the game is running
+///
+/// Session record is kept in the `.open` file next to the history file
+/// and should be updated periodically. If the launcher was closed before
+/// the game, this record is moved to the history when the next session
+/// starts, so the played time is not lost
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use anime_launcher_sdk::launch::keywords::Keywords;
+/// use anime_launcher_sdk::launch::playtime::*;
+///
+/// let path = std::env::temp_dir().join(format!("playtime-session-{}.jsonl", std::process::id()));
+///
+/// let mut record = PlaytimeRecord::from_keywords(&Keywords::new().with("edition", "global"));
+///
+/// // Session was started an hour ago, and the launcher was killed
+/// record.started_at -= 3600;
+///
+/// let mut session = PlaytimeSession::start(&path, record.clone()).unwrap();
+///
+/// session.update().unwrap();
+///
+/// // Unfinished session is counted by the history
+/// assert!(PlaytimeHistory::load(&path).unwrap().total() >= Duration::from_secs(3600));
+///
+/// // And it's saved to the history when the next session starts
+/// let session = PlaytimeSession::start(&path, record).unwrap();
+///
+/// assert_eq!(PlaytimeHistory::load(&path).unwrap().records.len(), 2);
+///
+/// session.discard().unwrap();
+///
+/// assert_eq!(PlaytimeHistory::load(&path).unwrap().records.len(), 1);
+///
+/// std::fs::remove_file(path).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct PlaytimeSession {
+    history: PathBuf,
+    record: PlaytimeRecord
+}
+
+impl PlaytimeSession {
+    /// Get path of the unfinished session file of the given history file
+    #[inline]
+    fn get_file(history: &Path) -> PathBuf {
+        history.with_extension("open")
+    }
+
+    /// Read unfinished session of the given history file
+    fn read_unfinished(history: &Path) -> anyhow::Result<Option<PlaytimeRecord>> {
+        let file = Self::get_file(history);
+
+        if !file.exists() {
+            return Ok(None);
+        }
+
+        match serde_json::from_slice(&std::fs::read(file)?) {
+            Ok(record) => Ok(Some(record)),
+
+            Err(err) => {
+                tracing::warn!("Skipping broken unfinished playtime record: {err}");
+
+                Ok(None)
+            }
+        }
+    }
+
+    /// Start the session and save its record
+    ///
+    /// Unfinished session left by the previous launch is
+    /// moved to the history first
+    pub fn start(history: impl Into<PathBuf>, record: PlaytimeRecord) -> anyhow::Result<Self> {
+        let history = history.into();
+
+        if let Some(unfinished) = Self::read_unfinished(&history)? {
+            tracing::warn!("Saving unfinished game session started at {}", format_time(unfinished.start_time()));
+
+            PlaytimeHistory::append(&history, &unfinished)?;
+        }
+
+        let session = Self {
+            history,
+            record
+        };
+
+        session.save()?;
+
+        Ok(session)
+    }
+
+    #[inline]
+    pub fn record(&self) -> &PlaytimeRecord {
+        &self.record
+    }
+
+    /// Count the time passed since the session start
+    pub fn update(&mut self) -> anyhow::Result<()> {
+        self.record.touch();
+
+        self.save()
+    }
+
+    /// Save the session to the history with the given outcome
+    pub fn finish(mut self, outcome: &RunOutcome) -> anyhow::Result<()> {
+        self.record.finish(outcome);
+
+        PlaytimeHistory::append(&self.history, &self.record)?;
+
+        self.discard()
+    }
+
+    /// Remove the session without saving it to the history,
+    /// e.g. when the game has failed to start
+    pub fn discard(self) -> anyhow::Result<()> {
+        let file = Self::get_file(&self.history);
+
+        if file.exists() {
+            std::fs::remove_file(file)?;
+        }
+
+        Ok(())
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        let file = Self::get_file(&self.history);
+
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        // Replace the file at once so it's never left half-written
+        let temp = file.with_extension("open.tmp");
+
+        std::fs::write(&temp, serde_json::to_vec(&self.record)?)?;
+        std::fs::rename(temp, file)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,305 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Max number of parents checked by `ProcessTree::contains_process`
+const MAX_PARENT_CHAIN: usize = 64;
+
+/// Information about the process read from `/proc/<pid>/stat`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ProcessStat {
+    pub parent: u32,
+    pub group: u32,
+    pub zombie: bool,
+
+    /// Time the process started after the system boot, in clock ticks.
+    /// Used to tell the process from a new one which got the same PID
+    pub start_time: u64
+}
+
+impl ProcessStat {
+    fn read(pid: u32) -> Option<Self> {
+        let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+
+        // Process name can contain spaces and parentheses
+        // so we skip everything before the last ')'
+        let (_, stat) = stat.rsplit_once(')')?;
+
+        let mut fields = stat.split_whitespace();
+
+        let state = fields.next()?;
+        let parent = fields.next()?.parse().ok()?;
+        let group = fields.next()?.parse().ok()?;
+
+        // Field 22 is the start time, we've read fields 3-5
+        let start_time = fields.nth(16)?.parse().ok()?;
+
+        Some(Self {
+            parent,
+            group,
+            zombie: state == "Z" || state == "X",
+            start_time
+        })
+    }
+}
+
+/// Tree of processes started by the launcher
+///
+/// The root process is expected to be started in its own process group,
+/// so every process it spawns belongs to this group unless it changes it.
+/// Processes which left the group are still tracked while their
+/// parent is alive, and later when they were reparented.
+/// Processes are identified by their PID and start time,
+/// so reused PIDs are not mistaken for the game processes
+///
+/// ```
+/// use std::process::Command;
+/// use std::os::unix::process::CommandExt;
+///
+/// use anime_launcher_sdk::launch::process::ProcessTree;
+///
+/// let mut child = Command::new("bash")
+///     .arg("-c")
+///     .arg("sleep 3 & sleep 1")
+///     .process_group(0)
+///     .spawn()
+///     .unwrap();
+///
+/// let start = std::time::Instant::now();
+///
+/// let mut tree = ProcessTree::new(child.id());
+///
+/// while child.try_wait().unwrap().is_none() || !tree.is_empty() {
+///     tree.update();
+///
+///     std::thread::sleep(std::time::Duration::from_millis(100));
+/// }
+///
+/// // Background process has outlived the spawned one
+/// assert!(start.elapsed().as_secs() >= 3);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessTree {
+    root: u32,
+    group: u32,
+
+    /// PIDs of the tracked processes and their start times
+    tracked: HashMap<u32, u64>
+}
+
+impl ProcessTree {
+    #[inline]
+    /// Track processes of the `root` process group and its descendants
+    pub fn new(root: u32) -> Self {
+        let start_time = ProcessStat::read(root)
+            .map(|stat| stat.start_time)
+            .unwrap_or_default();
+
+        Self {
+            root,
+            group: root,
+            tracked: HashMap::from([(root, start_time)])
+        }
+    }
+
+    #[inline]
+    /// PID of the root process
+    pub fn root(&self) -> u32 {
+        self.root
+    }
+
+    #[inline]
+    /// Process group of the root process
+    pub fn group(&self) -> u32 {
+        self.group
+    }
+
+    #[inline]
+    /// PIDs of the currently running processes
+    ///
+    /// Call `update` to refresh this list
+    pub fn pids(&self) -> HashSet<u32> {
+        self.tracked.keys().copied().collect()
+    }
+
+    #[inline]
+    /// Check if all the tracked processes have exited
+    ///
+    /// Call `update` to refresh this value
+    pub fn is_empty(&self) -> bool {
+        self.tracked.is_empty()
+    }
+
+    /// Check if the process is currently a member of the tree process group
+    pub fn is_group_member(&self, pid: u32) -> bool {
+        ProcessStat::read(pid).is_some_and(|stat| !stat.zombie && stat.group == self.group)
+    }
+
+    /// Check if the process still belongs to the tree
+    ///
+    /// Process is read from `/proc` again, so it must be a member of the tree
+    /// process group or a descendant of a member of this group. Processes which
+    /// have left the group and were reparented are not matched
+    ///
+    /// ```
+    /// use std::process::Command;
+    /// use std::os::unix::process::CommandExt;
+    ///
+    /// use anime_launcher_sdk::launch::process::ProcessTree;
+    ///
+    /// let mut child = Command::new("sleep")
+    ///     .arg("3")
+    ///     .process_group(0)
+    ///     .spawn()
+    ///     .unwrap();
+    ///
+    /// let tree = ProcessTree::new(child.id());
+    ///
+    /// assert!(tree.contains_process(child.id()));
+    /// assert!(!tree.contains_process(std::process::id()));
+    ///
+    /// child.kill().unwrap();
+    /// child.wait().unwrap();
+    ///
+    /// assert!(!tree.contains_process(child.id()));
+    /// ```
+    pub fn contains_process(&self, pid: u32) -> bool {
+        let Some(stat) = ProcessStat::read(pid).filter(|stat| !stat.zombie)
+        else {
+            return false;
+        };
+
+        // PID was reused by another process
+        if self.tracked.get(&pid).is_some_and(|start_time| *start_time != stat.start_time) {
+            return false;
+        }
+
+        if stat.group == self.group {
+            return true;
+        }
+
+        let mut parent = stat.parent;
+
+        // Limit the depth in case the tree is changed while we walk it
+        for _ in 0..MAX_PARENT_CHAIN {
+            if parent <= 1 {
+                return false;
+            }
+
+            let Some(stat) = ProcessStat::read(parent)
+            else {
+                return false;
+            };
+
+            if stat.group == self.group {
+                return true;
+            }
+
+            parent = stat.parent;
+        }
+
+        false
+    }
+
+    /// Scan `/proc` to update the list of running processes
+    pub fn update(&mut self) {
+        let Ok(entries) = Path::new("/proc").read_dir()
+        else {
+            self.tracked.clear();
+
+            return;
+        };
+
+        let processes = entries.flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+            .filter_map(|pid| Some((pid, ProcessStat::read(pid)?)))
+            .filter(|(_, stat)| !stat.zombie)
+            .collect::<HashMap<_, _>>();
+
+        // Forget exited processes and the ones whose PIDs were reused
+        self.tracked.retain(|pid, start_time| {
+            processes.get(pid).is_some_and(|stat| stat.start_time == *start_time)
+        });
+
+        // Add processes from our process group
+        for (pid, stat) in &processes {
+            if stat.group == self.group {
+                self.tracked.insert(*pid, stat.start_time);
+            }
+        }
+
+        // Add children of the tracked processes
+        loop {
+            let children = processes.iter()
+                .filter(|(pid, stat)| !self.tracked.contains_key(pid) && self.tracked.contains_key(&stat.parent))
+                .map(|(pid, stat)| (*pid, stat.start_time))
+                .collect::<Vec<_>>();
+
+            if children.is_empty() {
+                break;
+            }
+
+            self.tracked.extend(children);
+        }
+    }
+}
+
+fn check_signal_result(result: libc::c_int) -> std::io::Result<()> {
+    if result == 0 {
+        return Ok(());
+    }
+
+    let err = std::io::Error::last_os_error();
+
+    // Process has already exited
+    if err.raw_os_error() == Some(libc::ESRCH) {
+        return Ok(());
+    }
+
+    Err(err)
+}
+
+#[inline]
+/// Send signal to the process
+///
+/// Processes which have already exited are ignored
+///
+/// ```
+/// use std::process::Command;
+///
+/// use anime_launcher_sdk::launch::process::send_signal;
+///
+/// let mut child = Command::new("sleep").arg("5").spawn().unwrap();
+///
+/// send_signal(child.id(), libc::SIGTERM).unwrap();
+///
+/// assert!(!child.wait().unwrap().success());
+/// ```
+pub fn send_signal(pid: u32, signal: libc::c_int) -> std::io::Result<()> {
+    check_signal_result(unsafe { libc::kill(pid as libc::pid_t, signal) })
+}
+
+#[inline]
+/// Send signal to all the processes of the group
+///
+/// Groups without processes are ignored
+///
+/// ```
+/// use std::process::Command;
+/// use std::os::unix::process::CommandExt;
+///
+/// use anime_launcher_sdk::launch::process::send_group_signal;
+///
+/// let mut child = Command::new("bash")
+///     .arg("-c")
+///     .arg("sleep 5; true")
+///     .process_group(0)
+///     .spawn()
+///     .unwrap();
+///
+/// send_group_signal(child.id(), libc::SIGTERM).unwrap();
+///
+/// assert!(!child.wait().unwrap().success());
+/// ```
+pub fn send_group_signal(group: u32, signal: libc::c_int) -> std::io::Result<()> {
+    check_signal_result(unsafe { libc::killpg(group as libc::pid_t, signal) })
+}
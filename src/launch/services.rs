@@ -0,0 +1,203 @@
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::os::unix::process::CommandExt;
+use std::time::{Duration, Instant};
+
+use super::process;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Helper process started outside of the sandbox before the game
+/// and stopped when all the game processes have exited, e.g. `xdg-dbus-proxy`
+pub struct Service {
+    /// Name of the service used in logs
+    pub name: String,
+
+    /// Service binary followed by its arguments
+    pub command: Vec<String>,
+
+    /// Game is started only when this path appears, e.g. a socket created by the service.
+    /// The path is removed before starting and after stopping the service
+    pub ready_path: Option<PathBuf>,
+
+    /// Max time to wait for the `ready_path`
+    pub timeout: Duration
+}
+
+impl Service {
+    #[inline]
+    pub fn new<T: ToString>(name: impl ToString, command: impl IntoIterator<Item = T>) -> Self {
+        Self {
+            name: name.to_string(),
+            command: command.into_iter().map(|arg| arg.to_string()).collect(),
+            ready_path: None,
+            timeout: Duration::from_secs(5)
+        }
+    }
+
+    #[inline]
+    /// Wait until the given path appears after starting the service
+    pub fn with_ready_path(self, path: impl Into<PathBuf>) -> Self {
+        Self {
+            ready_path: Some(path.into()),
+            ..self
+        }
+    }
+
+    /// Start the service and wait until it's ready
+    ///
+    /// ```
+    /// use anime_launcher_sdk::launch::services::Service;
+    ///
+    /// let path = std::env::temp_dir().join("anime-launcher-sdk-service-test");
+    /// let path_str = path.to_string_lossy();
+    ///
+    /// // Create the file after a small delay and keep running
+    /// let mut service = Service::new("test", ["bash", "-c", "sleep 0.2 && touch \"$0\" && sleep 10", &*path_str])
+    ///     .with_ready_path(&path);
+    ///
+    /// let running = service.start().unwrap();
+    ///
+    /// assert!(path.exists());
+    ///
+    /// running.stop();
+    ///
+    /// assert!(!path.exists());
+    ///
+    /// // Service which doesn't create its path
+    /// service.ready_path = Some(path.with_extension("missing"));
+    /// service.timeout = std::time::Duration::from_millis(300);
+    ///
+    /// assert!(service.start().is_err());
+    /// ```
+    pub fn start(&self) -> anyhow::Result<RunningService> {
+        let Some(binary) = self.command.first()
+        else {
+            anyhow::bail!("Command of the {} service is empty", self.name);
+        };
+
+        // Stale path left by the previous run would make the service look ready
+        if let Some(path) = &self.ready_path {
+            if path.exists() {
+                std::fs::remove_file(path)?;
+            }
+        }
+
+        tracing::info!("Starting {} service: {}", self.name, crate::shell::join(&self.command));
+
+        // Own process group allows to stop processes started by the service
+        let child = Command::new(binary)
+            .args(&self.command[1..])
+            .stdin(Stdio::null())
+            .process_group(0)
+            .spawn()
+            .map_err(|err| anyhow::anyhow!("Failed to start {} service: {err}", self.name))?;
+
+        let mut service = RunningService {
+            name: self.name.clone(),
+            child,
+            ready_path: self.ready_path.clone()
+        };
+
+        let Some(path) = &self.ready_path
+        else {
+            return Ok(service);
+        };
+
+        let start = Instant::now();
+
+        while !path.exists() {
+            if let Some(status) = service.child.try_wait()? {
+                service.stop();
+
+                anyhow::bail!("{} service has exited with {status}", self.name);
+            }
+
+            if start.elapsed() >= self.timeout {
+                service.stop();
+
+                anyhow::bail!("{} service is not ready after {} ms", self.name, self.timeout.as_millis());
+            }
+
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        Ok(service)
+    }
+}
+
+#[derive(Debug)]
+/// Started service
+pub struct RunningService {
+    name: String,
+    child: Child,
+    ready_path: Option<PathBuf>
+}
+
+impl RunningService {
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Stop the service processes and remove its ready path
+    ///
+    /// `SIGTERM` is sent to the whole process group of the service,
+    /// and the service is killed if it's still running after a second
+    pub fn stop(mut self) {
+        tracing::info!("Stopping {} service", self.name);
+
+        if let Err(err) = process::send_group_signal(self.child.id(), libc::SIGTERM) {
+            tracing::warn!("Failed to send SIGTERM to {} service: {err}", self.name);
+        }
+
+        let start = Instant::now();
+
+        while start.elapsed() < Duration::from_secs(1) {
+            if !matches!(self.child.try_wait(), Ok(None)) {
+                break;
+            }
+
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        if let Ok(None) = self.child.try_wait() {
+            if let Err(err) = self.child.kill() {
+                tracing::warn!("Failed to stop {} service: {err}", self.name);
+            }
+        }
+
+        let _ = self.child.wait();
+
+        if let Some(path) = &self.ready_path {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Start all the services
+///
+/// Already started services are stopped if some of them has failed
+pub fn start_services(services: &[Service]) -> anyhow::Result<Vec<RunningService>> {
+    let mut running = Vec::with_capacity(services.len());
+
+    for service in services {
+        match service.start() {
+            Ok(service) => running.push(service),
+
+            Err(err) => {
+                stop_services(running);
+
+                return Err(err);
+            }
+        }
+    }
+
+    Ok(running)
+}
+
+/// Stop all the given services in reverse order
+pub fn stop_services(services: Vec<RunningService>) {
+    for service in services.into_iter().rev() {
+        service.stop();
+    }
+}
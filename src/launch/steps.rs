@@ -0,0 +1,230 @@
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+
+#[cfg(feature = "sessions")]
+use std::path::Path;
+
+use crate::config::schema_blanks::prelude::{AllowedDrives, WineDrives};
+
+#[cfg(feature = "sessions")]
+use crate::sessions::SessionsExt;
+
+use super::logs::SessionLogs;
+use super::playtime::{PlaytimeRecord, PlaytimeHistory, PlaytimeSession, SystemClock};
+use super::{GameHandle, LaunchPlan, LaunchConfig, Suggestion};
+
+/// How often the running game session is saved
+/// so it's counted even if the launcher is killed
+const PLAYTIME_UPDATE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Sessions of the game, used to switch
+/// the wine prefix to the selected account
+#[cfg(feature = "sessions")]
+#[derive(Debug, Clone, Copy)]
+pub struct GameSessions {
+    pub get_current: fn() -> anyhow::Result<Option<String>>,
+    pub apply: fn(String, &Path) -> anyhow::Result<()>,
+    pub update: fn(String, &Path) -> anyhow::Result<()>
+}
+
+#[cfg(feature = "sessions")]
+impl GameSessions {
+    #[inline]
+    pub fn of<T: SessionsExt>() -> Self {
+        Self {
+            get_current: T::get_current,
+            apply: |name, prefix| T::apply(name, prefix),
+            update: |name, prefix| T::update(name, prefix)
+        }
+    }
+}
+
+/// Launch steps which differ between the games
+pub struct LaunchSteps<'a> {
+    /// Get telemetry server which is not disabled yet
+    pub telemetry: Box<dyn FnOnce() -> Option<String> + 'a>,
+
+    /// Prepare game files before the launch, e.g. download FPS unlocker
+    pub prepare: Option<Box<dyn FnOnce() -> anyhow::Result<()> + 'a>>,
+
+    /// Map Z: drive to the root folder. Needed by the game patches
+    /// which start the game from it
+    pub map_root_drive: bool,
+
+    /// Suggest the timeout fix if the game session was shorter than this
+    /// and the game created `driverError.log`
+    pub timeout_fix: Option<Duration>,
+
+    /// Folder of the game sessions logs
+    pub logs_dir: PathBuf,
+
+    /// Link to the latest session log
+    pub game_log: PathBuf,
+
+    pub log_limit: usize,
+
+    /// Playtime history file of the game
+    pub playtime_file: PathBuf,
+
+    #[cfg(feature = "sessions")]
+    pub sessions: GameSessions
+}
+
+/// Start the game without blocking the current thread
+///
+/// Post-exit steps like sessions update are run by the returned handle
+/// when the game is closed
+pub fn launch_game(mut plan: LaunchPlan, config: &LaunchConfig, steps: LaunchSteps) -> anyhow::Result<GameHandle> {
+    // Playtime budget is chosen for the currently selected account
+    #[cfg(feature = "sessions")]
+    let account = (steps.sessions.get_current)()?;
+
+    #[cfg(not(feature = "sessions"))]
+    let account: Option<String> = None;
+
+    // Check playtime limits before preparing anything
+    let warning = Duration::from_secs(config.playtime_limits.warning_minutes * 60);
+
+    plan.time_limit = PlaytimeHistory::load(&steps.playtime_file)?
+        .get_allowance(config.playtime_limits, account.as_deref(), &SystemClock)?
+        .get_time_limit(warning)?;
+
+    // Check telemetry servers (skipped when the user opted out of
+    // automatic telemetry disabling, or when they're blocked in the sandbox)
+
+    #[cfg(feature = "sandbox")]
+    let blocked_in_sandbox = config.sandbox.blocks_telemetry();
+
+    #[cfg(not(feature = "sandbox"))]
+    let blocked_in_sandbox = false;
+
+    if config.disable_telemetry && blocked_in_sandbox {
+        tracing::info!("Telemetry servers are blocked in the sandbox hosts file");
+    }
+
+    else if config.disable_telemetry {
+        tracing::info!("Checking telemetry");
+
+        if let Some(server) = (steps.telemetry)() {
+            return Err(anyhow::anyhow!("Telemetry server is not disabled: {server}"));
+        }
+    }
+
+    else {
+        tracing::info!("Telemetry check is disabled in the launcher settings");
+    }
+
+    if let Some(prepare) = steps.prepare {
+        prepare()?;
+    }
+
+    // Prepare wine prefix drives
+    config.drives.map_folders(&config.game, config.prefix)?;
+
+    #[cfg(feature = "sandbox")]
+    let sandboxed = config.sandbox.is_enabled();
+
+    #[cfg(not(feature = "sandbox"))]
+    let sandboxed = false;
+
+    // Workaround for the game patch and for sandboxing feature
+    if steps.map_root_drive || sandboxed {
+        WineDrives::map_folder(config.prefix, AllowedDrives::Z, "/")?;
+    }
+
+    if sandboxed {
+        WineDrives::map_folder(config.prefix, AllowedDrives::C, "../drive_c")?;
+    }
+
+    // Delete driverError.log so we can detect if timeout fix is needed
+    let driver_error_log = config.game.join("driverError.log");
+
+    if steps.timeout_fix.is_some() {
+        let _ = std::fs::remove_file(&driver_error_log);
+    }
+
+    #[cfg(feature = "sessions")]
+    if let Some(current) = (steps.sessions.get_current)()? {
+        (steps.sessions.apply)(current, config.prefix)?;
+    }
+
+    // Run command
+
+    let log = SessionLogs::new(steps.logs_dir)
+        .with_retention(config.logs.retention as usize)
+        .with_compression(config.logs.compress)
+        .create()?;
+
+    // Keep game.log pointing to the latest session log
+    log.link_to(steps.game_log)?;
+
+    #[cfg(feature = "sessions")]
+    let (sessions, prefix) = (steps.sessions, config.prefix.to_path_buf());
+
+    // Session is counted by the playtime limits from its start,
+    // and saved to the playtime history when the game is closed
+    let mut playtime = PlaytimeRecord::from_keywords(&plan.keywords);
+
+    playtime.account = account;
+
+    let playtime = Arc::new(Mutex::new(Some(PlaytimeSession::start(steps.playtime_file, playtime)?)));
+
+    // Update the session until the sender is dropped
+    let (stop_updates, updates) = mpsc::channel::<()>();
+
+    {
+        let playtime = playtime.clone();
+
+        std::thread::spawn(move || {
+            while let Err(mpsc::RecvTimeoutError::Timeout) = updates.recv_timeout(PLAYTIME_UPDATE_INTERVAL) {
+                if let Some(session) = playtime.lock().unwrap().as_mut() {
+                    if let Err(err) = session.update() {
+                        tracing::warn!("Failed to update the playtime session: {err}");
+                    }
+                }
+            }
+        });
+    }
+
+    let timeout_fix = config.timeout_fix;
+    let timeout_fix_threshold = steps.timeout_fix;
+
+    let finished_playtime = playtime.clone();
+
+    let handle = plan.spawn(&log.path, steps.log_limit, move |outcome| {
+        drop(stop_updates);
+
+        if let Some(session) = finished_playtime.lock().unwrap().take() {
+            if let Err(err) = session.finish(outcome) {
+                tracing::error!("Failed to save the session to the playtime history: {err}");
+            }
+        }
+
+        #[cfg(feature = "sessions")]
+        if let Some(current) = (sessions.get_current)()? {
+            (sessions.update)(current, &prefix)?;
+        }
+
+        // Suggest enabling the timeout fix if:
+        // - not already enabled
+        // - game exited faster than the game specific threshold
+        // - driverError.log was recreated during the session
+        if let Some(threshold) = timeout_fix_threshold {
+            if !timeout_fix && outcome.duration < threshold && driver_error_log.exists() {
+                outcome.suggestions.push(Suggestion::EnableTimeoutFix);
+            }
+        }
+
+        Ok(())
+    });
+
+    // Game wasn't started so there's nothing to count
+    if handle.is_err() {
+        if let Some(session) = playtime.lock().unwrap().take() {
+            session.discard()?;
+        }
+    }
+
+    handle
+}
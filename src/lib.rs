@@ -1,10 +1,13 @@
-use std::process::{Command, Stdio};
+use std::path::{Path, PathBuf};
+use std::os::unix::fs::PermissionsExt;
 
 pub use anime_game_core;
 pub use wincompatlib;
 
 mod games;
 
+pub mod shell;
+
 #[cfg(feature = "genshin")]
 pub use games::genshin;
 
@@ -26,25 +29,55 @@ pub mod components;
 #[cfg(feature = "sessions")]
 pub mod sessions;
 
+#[cfg(feature = "game")]
+pub mod launch;
+
+#[cfg(feature = "game")]
+pub mod diagnostics;
+
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-// TODO: rewrite it to find this binary in PATH instead
+/// Check if the file exists and has some of the exec permission bits
+#[inline]
+fn is_executable(path: &Path) -> bool {
+    path.metadata().is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+/// Find executable binary by its name in the `PATH` folders
+///
+/// If the name contains `/` then it's checked as a path
+///
+/// ```
+/// assert!(anime_launcher_sdk::find_binary("bash").is_some());
+/// assert!(anime_launcher_sdk::find_binary("/bin/sh").is_some());
+/// assert!(anime_launcher_sdk::find_binary("an-anime-binary").is_none());
+/// assert!(anime_launcher_sdk::find_binary("/etc/passwd").is_none());
+/// ```
+pub fn find_binary(name: impl AsRef<str>) -> Option<PathBuf> {
+    let name = name.as_ref();
+
+    if name.contains('/') {
+        let path = PathBuf::from(name);
+
+        return is_executable(&path).then_some(path);
+    }
+
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|folder| folder.join(name))
+        .find(|path| is_executable(path))
+}
 
 /// Check if specified binary is available
 /// 
 /// ```
 /// assert!(anime_launcher_sdk::is_available("bash"));
 /// ```
-#[allow(unused_must_use)]
 #[tracing::instrument(level = "trace", ret)]
 pub fn is_available(binary: &str) -> bool {
     tracing::trace!("Checking package availability");
 
-    let Ok(mut child) = Command::new(binary).stdout(Stdio::null()).stderr(Stdio::null()).spawn() else {
-        return false;
-    };
-
-    child.kill();
-
-    true
+    find_binary(binary).is_some()
 }
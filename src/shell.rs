@@ -0,0 +1,153 @@
+//! Helpers to build `bash` commands from untrusted values
+//!
+//! Paths of the game, wine prefix and other folders can contain spaces,
+//! quotes, `$` and other symbols which have special meaning for the shell,
+//! so they must never be pasted into commands as is.
+
+/// Quote given string so it can be safely passed to the shell as one argument
+///
+/// ```
+/// use anime_launcher_sdk::shell::quote;
+///
+/// assert_eq!(quote("wine64"), "wine64");
+/// assert_eq!(quote("/games/An Anime Game"), "'/games/An Anime Game'");
+/// assert_eq!(quote("it's"), "'it'\\''s'");
+/// assert_eq!(quote("$HOME"), "'$HOME'");
+/// assert_eq!(quote(""), "''");
+/// ```
+pub fn quote(value: impl AsRef<str>) -> String {
+    let value = value.as_ref();
+
+    let is_safe = !value.is_empty() && value.chars().all(|c| {
+        c.is_ascii_alphanumeric() || "_-+=/.,:@%".contains(c)
+    });
+
+    if is_safe {
+        value.to_string()
+    }
+
+    else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+/// Quote every argument and join them with spaces
+///
+/// ```
+/// use anime_launcher_sdk::shell::join;
+///
+/// assert_eq!(join(["wine64", "/games/it's me/game.exe"]), "wine64 '/games/it'\\''s me/game.exe'");
+///
+/// // Check that bash gets exactly the same arguments back
+/// let hostile = "/games/\"quoted\" $(rm -rf ~) `id` it's\\here";
+///
+/// let output = std::process::Command::new("bash")
+///     .arg("-c")
+///     .arg(join(["printf", "%s\\n", hostile, "second arg"]))
+///     .output()
+///     .unwrap();
+///
+/// assert_eq!(String::from_utf8_lossy(&output.stdout), format!("{hostile}\nsecond arg\n"));
+/// ```
+pub fn join<T: AsRef<str>>(args: impl IntoIterator<Item = T>) -> String {
+    args.into_iter()
+        .map(quote)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Split command into arguments the same way as the shell would do
+///
+/// Supports single and double quotes and backslash escapes.
+/// Doesn't perform variables expansion
+///
+/// ```
+/// use anime_launcher_sdk::shell::split;
+///
+/// assert_eq!(split("'%build%/proton' run").unwrap(), ["%build%/proton", "run"]);
+/// assert_eq!(split("--flag \"a \\\"b\\\" $c\" d\\ e").unwrap(), ["--flag", "a \"b\" $c", "d e"]);
+/// assert_eq!(split("  ").unwrap(), Vec::<String>::new());
+///
+/// assert!(split("'unclosed").is_err());
+/// ```
+pub fn split(command: impl AsRef<str>) -> anyhow::Result<Vec<String>> {
+    let mut args = Vec::new();
+
+    let mut current = String::new();
+    let mut has_arg = false;
+
+    let mut chars = command.as_ref().chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                has_arg = true;
+
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => anyhow::bail!("Unclosed single quote in command")
+                    }
+                }
+            }
+
+            '"' => {
+                has_arg = true;
+
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => current.push(c),
+                            Some('\n') => (),
+
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+
+                            None => anyhow::bail!("Unclosed double quote in command")
+                        }
+
+                        Some(c) => current.push(c),
+                        None => anyhow::bail!("Unclosed double quote in command")
+                    }
+                }
+            }
+
+            '\\' => match chars.next() {
+                Some('\n') => (),
+
+                Some(c) => {
+                    has_arg = true;
+
+                    current.push(c);
+                }
+
+                None => anyhow::bail!("Command ends with a backslash")
+            }
+
+            c if c.is_whitespace() => {
+                if has_arg {
+                    args.push(std::mem::take(&mut current));
+
+                    has_arg = false;
+                }
+            }
+
+            c => {
+                has_arg = true;
+
+                current.push(c);
+            }
+        }
+    }
+
+    if has_arg {
+        args.push(current);
+    }
+
+    Ok(args)
+}